@@ -0,0 +1,369 @@
+//! The schema of kinds and mappings exported by InfoAPI through `Doc::export`.
+//!
+//! Schemas from multiple sources are merged into [`All`],
+//! which records the conflicts between them.
+//! This crate has no dependency on the browser,
+//! so it can be used by both the template editor and native tools.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::Arc,
+};
+
+use serde::Deserialize;
+
+mod name;
+pub use name::{MappingName, QualifiedRef, FQN_SEPARATOR};
+
+mod source;
+pub use source::{parse_source, SourceInfo, SourceSchema, FORMAT_VERSION};
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct KindId(pub String);
+impl KindId {
+    pub const STRING: &'static str = "infoapi/string";
+    pub const INTEGER: &'static str = "infoapi/integer";
+    pub const FLOAT: &'static str = "infoapi/float";
+    pub const BOOL: &'static str = "infoapi/bool";
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KindDef {
+    #[serde(default)]
+    pub help: String,
+    #[serde(default)]
+    pub can_display: bool,
+    #[serde(default)]
+    pub metadata: KnownKindMetadata,
+    /// The source that this kind was loaded from.
+    #[serde(skip)]
+    pub origin: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KnownKindMetadata {
+    #[serde(default, rename = "infoapi/is-root")]
+    pub is_root: bool,
+    #[serde(default, rename = "infoapi:browser/template-name")]
+    pub template_name: Option<String>,
+    #[serde(default, rename = "infoapi/source-plugin")]
+    pub source_plugin: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingDef {
+    pub source_kind: KindId,
+    pub target_kind: KindId,
+    pub name: MappingName,
+    pub is_implicit: bool,
+    pub parameters: Vec<ParamDef>,
+    pub mutable: bool,
+    pub help: String,
+    #[serde(default)]
+    pub metadata: KnownMappingMetadata,
+    /// The source that this mapping was loaded from.
+    #[serde(skip)]
+    pub origin: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct KnownMappingMetadata {
+    #[serde(default, rename = "infoapi/source-plugin")]
+    pub source_plugin: Option<String>,
+    #[serde(default, rename = "infoapi/alias-of")]
+    pub alias_of: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct ParamName(pub String);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParamDef {
+    pub name: ParamName,
+    pub kind: KindId,
+    pub multi: bool,
+    pub optional: bool,
+}
+
+/// Formats the parameter as `name: kind` with `?` for optional and `...` for multi parameters.
+impl fmt::Display for ParamDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}: {}{}",
+            self.name.0,
+            if self.optional { "?" } else { "" },
+            self.kind.0,
+            if self.multi { "..." } else { "" },
+        )
+    }
+}
+
+/// Two sources define the same kind or mapping differently.
+///
+/// The definition from the later source is used.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub subject: ConflictSubject,
+    pub old_origin: String,
+    pub new_origin: String,
+    pub differences: Vec<Difference>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConflictSubject {
+    Kind(KindId),
+    Mapping(KindId, MappingName),
+}
+
+impl fmt::Display for ConflictSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Kind(kind) => write!(f, "kind {}", kind.0),
+            Self::Mapping(kind, name) => write!(f, "mapping {} on {}", name.0, kind.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Difference {
+    Help,
+    CanDisplay {
+        old: bool,
+        new: bool,
+    },
+    TargetKind {
+        old: KindId,
+        new: KindId,
+    },
+    IsImplicit {
+        old: bool,
+        new: bool,
+    },
+    Mutable {
+        old: bool,
+        new: bool,
+    },
+    AliasOf {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Parameters {
+        old: String,
+        new: String,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Help => write!(f, "help text differs"),
+            Self::CanDisplay { old, new } => write!(f, "canDisplay changed from {old} to {new}"),
+            Self::TargetKind { old, new } => {
+                write!(f, "target kind changed from {} to {}", old.0, new.0)
+            }
+            Self::IsImplicit { old, new } => write!(f, "isImplicit changed from {old} to {new}"),
+            Self::Mutable { old, new } => write!(f, "mutable changed from {old} to {new}"),
+            Self::AliasOf { old, new } => write!(
+                f,
+                "alias changed from {} to {}",
+                old.as_deref().unwrap_or("none"),
+                new.as_deref().unwrap_or("none")
+            ),
+            Self::Parameters { old, new } => {
+                write!(f, "parameters changed from ({old}) to ({new})")
+            }
+        }
+    }
+}
+
+fn kind_differences(old: &KindDef, new: &KindDef) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    if old.help != new.help {
+        differences.push(Difference::Help);
+    }
+    if old.can_display != new.can_display {
+        differences.push(Difference::CanDisplay {
+            old: old.can_display,
+            new: new.can_display,
+        });
+    }
+    differences
+}
+
+fn mapping_differences(old: &MappingDef, new: &MappingDef) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    if old.help != new.help {
+        differences.push(Difference::Help);
+    }
+    if old.target_kind != new.target_kind {
+        differences.push(Difference::TargetKind {
+            old: old.target_kind.clone(),
+            new: new.target_kind.clone(),
+        });
+    }
+    if old.is_implicit != new.is_implicit {
+        differences.push(Difference::IsImplicit {
+            old: old.is_implicit,
+            new: new.is_implicit,
+        });
+    }
+    if old.mutable != new.mutable {
+        differences.push(Difference::Mutable {
+            old: old.mutable,
+            new: new.mutable,
+        });
+    }
+    if old.metadata.alias_of != new.metadata.alias_of {
+        differences.push(Difference::AliasOf {
+            old: old.metadata.alias_of.clone(),
+            new: new.metadata.alias_of.clone(),
+        });
+    }
+    let (old_params, new_params) = (params_string(old), params_string(new));
+    if old_params != new_params {
+        differences.push(Difference::Parameters {
+            old: old_params,
+            new: new_params,
+        });
+    }
+    differences
+}
+
+fn params_string(mapping: &MappingDef) -> String {
+    let params: Vec<_> = mapping.parameters.iter().map(ToString::to_string).collect();
+    params.join(", ")
+}
+
+#[derive(Clone, Default)]
+pub struct All {
+    pub kinds: BTreeMap<KindId, KindDef>,
+    pub mappings: BTreeMap<KindId, BTreeMap<MappingName, Arc<MappingDef>>>,
+    pub known_plugins: BTreeSet<String>,
+    pub errors: Vec<Arc<anyhow::Error>>,
+    pub conflicts: Vec<Conflict>,
+    /// The sources that were loaded successfully.
+    pub sources: Vec<SourceInfo>,
+}
+
+impl All {
+    pub fn can_display(&self, kind: &KindId) -> bool {
+        self.kinds.get(kind).is_some_and(|def| def.can_display)
+    }
+
+    /// Whether an expression resolving to `kind` is accepted,
+    /// where top-level expressions (`expect_kind` is `None`) must be displayable.
+    pub fn admits(&self, kind: &KindId, expect_kind: Option<&KindId>) -> bool {
+        match expect_kind {
+            Some(expect_kind) => kind == expect_kind,
+            None => self.can_display(kind),
+        }
+    }
+
+    /// The mapping that `mapping` is an alias of, if any.
+    ///
+    /// Aliases are registered on the same source kind as the canonical mapping.
+    pub fn canonical_of(&self, mapping: &MappingDef) -> Option<&Arc<MappingDef>> {
+        let alias_of = mapping.metadata.alias_of.as_ref()?;
+        self.mappings
+            .get(&mapping.source_kind)?
+            .get(&MappingName(alias_of.clone()))
+    }
+
+    /// The other names registered for the canonical mapping `mapping`.
+    pub fn aliases_of<'t>(
+        &'t self,
+        mapping: &'t MappingDef,
+    ) -> impl Iterator<Item = &'t Arc<MappingDef>> + 't {
+        self.mappings
+            .get(&mapping.source_kind)
+            .into_iter()
+            .flat_map(|mappings| mappings.values())
+            .filter(|other| other.metadata.alias_of.as_ref() == Some(&mapping.name.0))
+    }
+}
+
+impl All {
+    /// Records a redefinition unless both definitions are identical,
+    /// e.g. when the same schema is loaded twice.
+    fn record_conflict(
+        &mut self,
+        subject: ConflictSubject,
+        old_origin: &str,
+        new_origin: &str,
+        differences: Vec<Difference>,
+    ) {
+        if !differences.is_empty() {
+            self.conflicts.push(Conflict {
+                subject,
+                old_origin: old_origin.to_string(),
+                new_origin: new_origin.to_string(),
+                differences,
+            });
+        }
+    }
+}
+
+impl Extend<anyhow::Result<SourceSchema>> for All {
+    fn extend<T: IntoIterator<Item = anyhow::Result<SourceSchema>>>(&mut self, iter: T) {
+        for schema in iter {
+            match schema {
+                Ok(schema) => {
+                    let origin = &schema.info.origin;
+                    self.errors.extend(schema.skipped.into_iter().map(|err| {
+                        Arc::new(err.context(format!("skipped part of schema from {origin}")))
+                    }));
+
+                    for (kind, mut def) in schema.kinds {
+                        def.origin = origin.clone();
+                        let differences = self
+                            .kinds
+                            .get(&kind)
+                            .map(|old| (old.origin.clone(), kind_differences(old, &def)));
+                        if let Some((old_origin, differences)) = differences {
+                            self.record_conflict(
+                                ConflictSubject::Kind(kind.clone()),
+                                &old_origin,
+                                &def.origin,
+                                differences,
+                            );
+                        }
+                        self.kinds.insert(kind, def);
+                    }
+
+                    for mut mapping in schema.mappings {
+                        if let Some(plugin) = &mapping.metadata.source_plugin {
+                            self.known_plugins.insert(plugin.clone());
+                        }
+
+                        mapping.origin = origin.clone();
+                        let mapping = Arc::new(mapping);
+                        let old = self
+                            .mappings
+                            .entry(mapping.source_kind.clone())
+                            .or_default()
+                            .insert(mapping.name.clone(), mapping.clone());
+                        if let Some(old) = old {
+                            self.record_conflict(
+                                ConflictSubject::Mapping(
+                                    mapping.source_kind.clone(),
+                                    mapping.name.clone(),
+                                ),
+                                &old.origin,
+                                &mapping.origin,
+                                mapping_differences(&old, &mapping),
+                            );
+                        }
+                    }
+
+                    self.sources.push(schema.info);
+                }
+                Err(err) => self.errors.push(Arc::new(err)),
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Fully qualified mapping names and the references to them written in templates.
+
+use std::fmt;
+
+use serde::Deserialize;
+
+/// The separator between tokens of a fully-qualified name.
+pub const FQN_SEPARATOR: &str = ":";
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MappingName(pub String);
+impl MappingName {
+    pub fn last(&self) -> &str {
+        self.0.split(':').next_back().expect("split is nonempty")
+    }
+
+    /// Tests if `input` matches this name, same as `FullyQualifiedName::match` in `lib/name.php`.
+    ///
+    /// Returns `None` if it does not match,
+    /// or the number of tokens missing from `input` if it matches.
+    pub fn matches(&self, input: &QualifiedRef) -> Option<usize> {
+        if input.last() != self.last() {
+            return None;
+        }
+
+        let mut input = input.0.iter().peekable();
+        let mut missing = 0;
+        for token in self.0.split(':') {
+            if input.peek().map(|s| s.as_str()) == Some(token) {
+                _ = input.next();
+            } else {
+                missing += 1;
+            }
+        }
+
+        if input.next().is_some() {
+            // the next input token does not match any tokens in this name
+            return None;
+        }
+
+        Some(missing)
+    }
+
+    pub fn minify<'t>(&'t self, others: impl Iterator<Item = &'t MappingName>) -> String {
+        let my_last = self.last();
+        let mut collisions: Vec<Vec<_>> = others
+            .filter(|other| other.0 != self.0 && other.last() == my_last)
+            .map(|other| other.0.split(':').collect())
+            .collect();
+
+        let mut my_pieces: Vec<_> = self.0.split(':').collect();
+        _ = my_pieces.pop();
+        let mut use_prefixes = 0;
+
+        fn has_subprefix(haystack: &[&str], needle: &[&str]) -> bool {
+            let mut needle = needle.iter().copied().peekable();
+            for &haystack_part in haystack {
+                let Some(&needle_part) = needle.peek() else {
+                    // needle is fully removed
+                    return true;
+                };
+                if haystack_part == needle_part {
+                    _ = needle.next();
+                }
+            }
+
+            needle.next().is_none()
+        }
+
+        while !collisions.is_empty() {
+            use_prefixes += 1;
+            if use_prefixes > my_pieces.len() {
+                // subset of another, cannot fully qualify
+                break;
+            }
+
+            collisions.retain(|other| {
+                has_subprefix(&other[..other.len() - 1], &my_pieces[..use_prefixes])
+            })
+        }
+
+        let mut out = my_pieces;
+        out.truncate(use_prefixes);
+        out.push(my_last);
+        out.join(":")
+    }
+}
+
+/// A partially qualified reference to a mapping, e.g. `a:b` in `{a:b}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualifiedRef(pub Vec<String>);
+impl QualifiedRef {
+    pub fn parse(text: &str) -> Self {
+        Self(text.split(FQN_SEPARATOR).map(str::to_string).collect())
+    }
+
+    pub fn last(&self) -> &str {
+        self.0.last().expect("qualified ref must be nonempty")
+    }
+}
+
+impl fmt::Display for QualifiedRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(FQN_SEPARATOR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(name: &str, input: &str) -> Option<usize> {
+        MappingName(name.into()).matches(&QualifiedRef::parse(input))
+    }
+
+    #[test]
+    fn exact_name() {
+        assert_eq!(
+            matches("infoapi:player:name", "infoapi:player:name"),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn last_token_only() {
+        assert_eq!(matches("infoapi:player:name", "name"), Some(2));
+    }
+
+    #[test]
+    fn missing_tokens() {
+        assert_eq!(matches("infoapi:player:name", "player:name"), Some(1));
+        assert_eq!(matches("infoapi:player:name", "infoapi:name"), Some(1));
+    }
+
+    #[test]
+    fn non_matching_names() {
+        // the last token must be equal
+        assert_eq!(matches("infoapi:player:name", "infoapi:player"), None);
+        assert_eq!(matches("infoapi:player:name", "nam"), None);
+        // tokens that are not in the name
+        assert_eq!(matches("infoapi:player:name", "other:name"), None);
+        // tokens in the wrong order
+        assert_eq!(matches("infoapi:player:name", "player:infoapi:name"), None);
+        // more tokens than the name
+        assert_eq!(matches("player:name", "infoapi:player:name"), None);
+    }
+}
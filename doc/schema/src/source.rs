@@ -0,0 +1,130 @@
+//! Reading schemas exported by `Doc::export`.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+use serde::Deserialize as _;
+
+use crate::{KindDef, KindId, MappingDef};
+
+/// A single schema export, which is merged into [`All`](crate::All).
+pub struct SourceSchema {
+    pub info: SourceInfo,
+    pub kinds: BTreeMap<KindId, KindDef>,
+    pub mappings: Vec<MappingDef>,
+    /// Kinds and mappings that could not be read and are skipped.
+    pub skipped: Vec<anyhow::Error>,
+}
+
+/// The newest `formatVersion` produced by `Doc::export` that this crate understands.
+pub const FORMAT_VERSION: u64 = 2;
+
+/// Describes where a schema comes from, read from the envelope produced by `Doc::export`.
+#[derive(Debug, Clone)]
+pub struct SourceInfo {
+    /// Where the schema was loaded from, e.g. a URL or a file name.
+    pub origin: String,
+    /// The format version before migration.
+    pub format_version: u64,
+    pub infoapi_version: Option<String>,
+    /// The plugin or tool that exported the schema.
+    pub generator: Option<String>,
+}
+
+/// Parses a schema exported by `Doc::export`, loaded from `origin`.
+///
+/// Older formats are migrated to the current one.
+/// Kinds and mappings that cannot be read are skipped instead of failing the whole schema.
+pub fn parse_source(json: &str, origin: &str) -> anyhow::Result<SourceSchema> {
+    let value = serde_json::from_str(json).context("parse schema JSON")?;
+    let serde_json::Value::Object(mut envelope) = value else {
+        anyhow::bail!("schema must be a JSON object");
+    };
+
+    let format_version = match envelope.get("formatVersion") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .context("formatVersion must be a nonnegative integer")?,
+    };
+    anyhow::ensure!(
+        format_version <= FORMAT_VERSION,
+        "the schema uses format version {format_version}, \
+        but only versions up to {FORMAT_VERSION} are supported; \
+        the schema was probably generated by a newer InfoAPI version"
+    );
+    for version in format_version..FORMAT_VERSION {
+        envelope = match version {
+            1 => migrate_v1(envelope),
+            _ => anyhow::bail!("unsupported format version {version}"),
+        };
+    }
+
+    let string_field = |key| {
+        envelope
+            .get(key)
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+    };
+    let info = SourceInfo {
+        origin: origin.to_string(),
+        format_version,
+        infoapi_version: string_field("infoapiVersion"),
+        generator: string_field("generator"),
+    };
+
+    let schema = envelope
+        .remove("schema")
+        .context("missing schema in envelope")?;
+    let mut skipped = Vec::new();
+
+    let kinds = match schema.get("kinds") {
+        Some(serde_json::Value::Object(kinds)) => kinds.clone(),
+        // PHP encodes an empty associative array as `[]`
+        Some(serde_json::Value::Array(kinds)) if kinds.is_empty() => serde_json::Map::new(),
+        _ => anyhow::bail!("kinds must be an object"),
+    };
+    let kinds = kinds
+        .into_iter()
+        .filter_map(|(kind, def)| match serde_json::from_value(def) {
+            Ok(def) => Some((KindId(kind), def)),
+            Err(err) => {
+                skipped.push(anyhow::Error::new(err).context(format!("read kind {kind}")));
+                None
+            }
+        })
+        .collect();
+
+    let Some(serde_json::Value::Array(mappings)) = schema.get("mappings") else {
+        anyhow::bail!("mappings must be an array");
+    };
+    let mappings = mappings
+        .iter()
+        .filter_map(|mapping| match MappingDef::deserialize(mapping) {
+            Ok(mapping) => Some(mapping),
+            Err(err) => {
+                let name = mapping.get("name").and_then(serde_json::Value::as_str);
+                let name = name.unwrap_or("<unnamed>");
+                skipped.push(anyhow::Error::new(err).context(format!("read mapping {name}")));
+                None
+            }
+        })
+        .collect();
+
+    Ok(SourceSchema {
+        info,
+        kinds,
+        mappings,
+        skipped,
+    })
+}
+
+/// Format version 1 is the schema object itself without an envelope.
+fn migrate_v1(
+    schema: serde_json::Map<String, serde_json::Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut envelope = serde_json::Map::new();
+    envelope.insert("formatVersion".into(), 2.into());
+    envelope.insert("schema".into(), schema.into());
+    envelope
+}
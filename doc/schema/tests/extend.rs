@@ -0,0 +1,102 @@
+//! Merges schemas from multiple sources and records the conflicts between them.
+
+use infoapi_schema::{parse_source, All, ConflictSubject, KindId, MappingName};
+
+const EXAMPLE: &str = include_str!("fixtures/v2.json");
+
+/// Loads the example schema from `first`, then a copy changed by `edit` from `second`.
+fn load(edit: impl FnOnce(&mut serde_json::Value)) -> All {
+    let mut value: serde_json::Value = serde_json::from_str(EXAMPLE).unwrap();
+    edit(&mut value);
+    let first = parse_source(EXAMPLE, "first").unwrap();
+    let second = parse_source(&value.to_string(), "second").unwrap();
+
+    let mut schema = All::default();
+    schema.extend([Ok(first), Ok(second)]);
+    assert!(schema.errors.is_empty());
+    schema
+}
+
+/// The subject and the displayed differences of each conflict.
+fn conflicts(schema: &All) -> Vec<(String, Vec<String>)> {
+    schema
+        .conflicts
+        .iter()
+        .map(|conflict| {
+            assert_eq!(conflict.old_origin, "first");
+            assert_eq!(conflict.new_origin, "second");
+            let differences = conflict.differences.iter().map(|diff| diff.to_string());
+            (conflict.subject.to_string(), differences.collect())
+        })
+        .collect()
+}
+
+#[test]
+fn identical_sources_do_not_conflict() {
+    let schema = load(|_| {});
+    assert!(schema.conflicts.is_empty());
+    assert_eq!(schema.sources.len(), 2);
+}
+
+#[test]
+fn record_redefined_kinds() {
+    let schema = load(|value| {
+        let player = &mut value["schema"]["kinds"]["example/player"];
+        player["help"] = "A player".into();
+        player["canDisplay"] = true.into();
+    });
+
+    assert_eq!(
+        conflicts(&schema),
+        [(
+            "kind example/player".to_string(),
+            vec![
+                "help text differs".to_string(),
+                "canDisplay changed from false to true".to_string(),
+            ]
+        )]
+    );
+    // the later definition is used
+    assert!(schema.kinds[&KindId("example/player".into())].can_display);
+}
+
+#[test]
+fn record_redefined_mappings() {
+    let schema = load(|value| {
+        let mappings = &mut value["schema"]["mappings"];
+        mappings[0]["targetKind"] = "example/player".into();
+        mappings[0]["parameters"] = mappings[1]["parameters"].clone();
+        mappings[1]["isImplicit"] = true.into();
+        mappings[1]["mutable"] = false.into();
+        mappings[1]["metadata"]["infoapi/alias-of"].take();
+    });
+
+    assert_eq!(
+        conflicts(&schema),
+        [
+            (
+                "mapping example:name on example/player".to_string(),
+                vec![
+                    "target kind changed from infoapi/string to example/player".to_string(),
+                    "parameters changed from () to (fallback?: infoapi/string)".to_string(),
+                ]
+            ),
+            (
+                "mapping example:nick on example/player".to_string(),
+                vec![
+                    "isImplicit changed from false to true".to_string(),
+                    "mutable changed from true to false".to_string(),
+                    "alias changed from example:name to none".to_string(),
+                ]
+            ),
+        ]
+    );
+
+    let ConflictSubject::Mapping(kind, name) = &schema.conflicts[1].subject else {
+        panic!("expected a mapping conflict");
+    };
+    let nick = &schema.mappings[kind][name];
+    assert_eq!(name, &MappingName("example:nick".into()));
+    assert!(!nick.mutable);
+    assert_eq!(nick.origin, "second");
+}
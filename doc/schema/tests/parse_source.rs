@@ -0,0 +1,90 @@
+//! Reads schemas in each format exported by `Doc::export`.
+
+use infoapi_schema::{parse_source, All, KindId, FORMAT_VERSION};
+
+fn kind(id: &str) -> KindId {
+    KindId(id.into())
+}
+
+/// Checks the contents shared by `v1.json` and `v2.json`.
+fn assert_example_schema(json: &str) -> All {
+    let source = parse_source(json, "fixture").unwrap();
+    assert!(source.skipped.is_empty(), "{:?}", source.skipped);
+    assert_eq!(source.info.origin, "fixture");
+
+    let mut schema = All::default();
+    schema.extend([Ok(source)]);
+    assert!(schema.errors.is_empty());
+
+    let player = &schema.kinds[&kind("example/player")];
+    assert_eq!(player.help, "An online player");
+    assert!(!player.can_display);
+    assert_eq!(player.metadata.source_plugin.as_deref(), Some("Example"));
+    assert!(schema.kinds[&kind(KindId::STRING)].can_display);
+
+    let mappings: Vec<_> = schema.mappings[&kind("example/player")].values().collect();
+    assert_eq!(mappings.len(), 2);
+    let (name, nick) = (&mappings[0], &mappings[1]);
+    assert_eq!(name.name.0, "example:name");
+    assert_eq!(name.target_kind, kind(KindId::STRING));
+    assert!(!name.mutable);
+    assert_eq!(nick.metadata.alias_of.as_deref(), Some("example:name"));
+    assert!(nick.mutable);
+    assert_eq!(nick.parameters.len(), 1);
+    assert_eq!(nick.parameters[0].name.0, "fallback");
+    assert!(nick.parameters[0].optional);
+    assert!(!nick.parameters[0].multi);
+
+    schema
+}
+
+#[test]
+fn parse_v1_without_envelope() {
+    let schema = assert_example_schema(include_str!("fixtures/v1.json"));
+
+    let info = &schema.sources[0];
+    assert_eq!(info.format_version, 1);
+    assert_eq!(info.infoapi_version, None);
+    assert_eq!(info.generator, None);
+}
+
+#[test]
+fn parse_v2_envelope() {
+    let schema = assert_example_schema(include_str!("fixtures/v2.json"));
+
+    let info = &schema.sources[0];
+    assert_eq!(info.format_version, FORMAT_VERSION);
+    assert_eq!(info.infoapi_version.as_deref(), Some("2.0.0"));
+    assert_eq!(info.generator.as_deref(), Some("Example"));
+}
+
+#[test]
+fn skip_unreadable_mappings() {
+    let source = parse_source(include_str!("fixtures/v2-partial.json"), "fixture").unwrap();
+
+    // PHP encodes empty associative arrays as `[]`
+    assert!(source.kinds.is_empty());
+    assert_eq!(source.info.infoapi_version, None);
+    assert_eq!(source.mappings.len(), 1);
+    assert_eq!(source.mappings[0].name.0, "example:name");
+    assert_eq!(source.skipped.len(), 1);
+    assert!(format!("{:#}", source.skipped[0]).contains("example:broken"));
+}
+
+#[test]
+fn reject_newer_formats() {
+    let Err(err) = parse_source(include_str!("fixtures/v3.json"), "fixture") else {
+        panic!("format version 3 should be rejected");
+    };
+    assert!(format!("{err:#}").contains("format version 3"), "{err:#}");
+}
+
+#[test]
+fn share_schema_across_threads() {
+    let source = parse_source(include_str!("fixtures/v2.json"), "fixture");
+    let mut schema = All::default();
+    schema.extend([source]);
+
+    let kinds = std::thread::scope(|scope| scope.spawn(|| schema.kinds.len()).join().unwrap());
+    assert_eq!(kinds, 2);
+}
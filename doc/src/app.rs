@@ -0,0 +1,626 @@
+use std::{
+    borrow,
+    cell::RefCell,
+    collections::{hash_map, HashMap},
+    hash::Hash,
+    ops,
+    rc::Rc,
+};
+
+use anyhow::Context as _;
+use defy::defy;
+use yew::{prelude::*, suspense};
+use yew_router::prelude::*;
+
+use crate::{
+    components, data,
+    route::{EditorQuery, Route},
+    storage, util,
+};
+
+#[function_component]
+pub fn App() -> Html {
+    defy! {
+        HashRouter {
+            Suspense(fallback = fallback()) {
+                Main;
+            }
+        }
+    }
+}
+
+#[function_component]
+fn Main() -> HtmlResult {
+    let uploads = use_state(Vec::<components::Upload>::new);
+    let on_upload = Callback::from({
+        let uploads = uploads.clone();
+        move |new_uploads: Vec<components::Upload>| {
+            let mut uploads_value = (*uploads).clone();
+            uploads_value.extend(new_uploads);
+            uploads.set(uploads_value);
+        }
+    });
+
+    let source_settings = use_state(storage::load_source_settings);
+    let save_source_settings = Callback::from({
+        let source_settings = source_settings.clone();
+        move |settings: data::SourceSettings| {
+            storage::save_source_settings(&settings);
+            source_settings.set(settings);
+        }
+    });
+
+    // shared by the editor and the graph so that hidden plugins stay hidden across pages
+    let location = use_location();
+    let plugin_filter = use_state(|| PluginFilter::from_query(&initial_query(location)));
+
+    let fetched = suspense::use_future_with_deps(data::all, (*source_settings).clone())?;
+    let schema = use_memo(
+        |(fetched, uploads)| {
+            fetched.extended(uploads.iter().map(|upload| {
+                let contents = upload.contents.clone().map_err(anyhow::Error::msg);
+                contents
+                    .and_then(|contents| data::parse_source(&contents, &upload.file_name))
+                    .with_context(|| format!("load schema from file {}", upload.file_name))
+            }))
+        },
+        ((*fetched).clone(), (*uploads).clone()),
+    );
+
+    if schema.kinds.is_empty() {
+        return Ok(defy! {
+            div(class = "section") {
+                div(class = "container") {
+                    article(class = "message is-danger") {
+                        div(class = "message-header") {
+                            + "Error";
+                        }
+
+                        div(class = "message-body") {
+                            p { + "No schemas could be loaded."; }
+                            for err in &schema.errors {
+                                pre {
+                                    + format!("{err:?}");
+                                }
+                            }
+                        }
+                    }
+
+                    p(class = "block") {
+                        + "You can still load schema files exported by your server:";
+                    }
+                    components::SchemaUpload(on_upload = on_upload);
+
+                    p(class = "block mt-4") {
+                        + "Or configure additional sources:";
+                    }
+                    components::SourceSettingsForm(
+                        settings = (*source_settings).clone(),
+                        on_save = save_source_settings,
+                    );
+                }
+            }
+        });
+    }
+
+    let render = {
+        let schema = (*schema).clone();
+        let source_settings = (*source_settings).clone();
+        let plugins = (*plugin_filter).clone();
+        let on_plugins_change = util::set_state(&plugin_filter);
+        move |route| match route {
+            Route::Editor => defy! {
+                Editor(
+                    schema = schema.clone(),
+                    on_upload = on_upload.clone(),
+                    source_settings = source_settings.clone(),
+                    on_source_settings_change = save_source_settings.clone(),
+                    plugins = plugins.clone(),
+                    on_plugins_change = on_plugins_change.clone(),
+                );
+            },
+            Route::Kinds => defy! {
+                div(class = "section") {
+                    div(class = "container") {
+                        components::KindList(schema = schema.clone());
+                    }
+                }
+            },
+            Route::Kind { id } => defy! {
+                div(class = "section") {
+                    div(class = "container") {
+                        components::KindPage(schema = schema.clone(), kind = data::KindId(id));
+                    }
+                }
+            },
+            Route::Graph => defy! {
+                div(class = "section") {
+                    div(class = "container") {
+                        h1(class = "title") { + "Kind graph"; }
+                        p(class = "subtitle is-6") {
+                            + "Plugins hidden in the template editor are also hidden here.";
+                        }
+                        components::SchemaGraph(
+                            schema = schema.clone(),
+                            plugins = plugins.clone(),
+                        );
+                    }
+                }
+            },
+            Route::Diff => defy! {
+                div(class = "section") {
+                    div(class = "container") {
+                        h1(class = "title") { + "Schema diff"; }
+                        p(class = "subtitle is-6") {
+                            + "Check whether a plugin update breaks templates written for an older version.";
+                        }
+                        components::SchemaDiff(schema = schema.clone());
+                    }
+                }
+            },
+            Route::Mapping { name, kind } => defy! {
+                div(class = "section") {
+                    div(class = "container") {
+                        components::MappingPage(
+                            schema = schema.clone(),
+                            kind = data::KindId(kind),
+                            name = data::MappingName(name),
+                        );
+                    }
+                }
+            },
+        }
+    };
+
+    Ok(defy! {
+        Nav;
+        Switch<Route>(render = Callback::from(render));
+    })
+}
+
+#[function_component]
+fn Nav() -> Html {
+    let route = use_route::<Route>();
+    let is_editor = matches!(route, Some(Route::Editor));
+    let is_graph = matches!(route, Some(Route::Graph));
+    let is_diff = matches!(route, Some(Route::Diff));
+
+    defy! {
+        div(class = "container mt-4") {
+            div(class = "tabs") {
+                ul {
+                    li(class = classes![is_editor.then_some("is-active")]) {
+                        Link<Route>(to = Route::Editor) { + "Template editor"; }
+                    }
+                    li(class = classes![(!is_editor && !is_graph && !is_diff).then_some("is-active")]) {
+                        Link<Route>(to = Route::Kinds) { + "Kind reference"; }
+                    }
+                    li(class = classes![is_graph.then_some("is-active")]) {
+                        Link<Route>(to = Route::Graph) { + "Kind graph"; }
+                    }
+                    li(class = classes![is_diff.then_some("is-active")]) {
+                        Link<Route>(to = Route::Diff) { + "Schema diff"; }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[function_component]
+fn Editor(props: &EditorProps) -> Html {
+    let schema = &props.schema;
+
+    let navigator = use_navigator().expect("Editor is rendered in a router");
+    let location = use_location();
+    // only read on the first render, later changes to the URL come from this component itself
+    let query = use_state(|| initial_query(location));
+
+    let plugin_filter = &props.plugins;
+    let toggle_plugin = |plugin: &str| {
+        let plugin_filter = plugin_filter.clone();
+        let on_plugins_change = props.on_plugins_change.clone();
+        let plugin = plugin.to_string();
+        Callback::from(move |()| {
+            let mut plugin_filter_map = plugin_filter.clone();
+            plugin_filter_map.toggle(plugin.clone());
+            on_plugins_change.emit(plugin_filter_map);
+        })
+    };
+
+    let root_kinds: Vec<_> = schema
+        .kinds
+        .iter()
+        .filter(|(_, def)| def.metadata.is_root)
+        .map(|(kind, def)| {
+            let name = def.metadata.template_name.as_ref().unwrap_or(&def.help);
+            (kind.clone(), name.clone())
+        })
+        .collect();
+
+    let source_kind = use_state(|| find_root_kind(&root_kinds, &query));
+    // template kinds may only become available later, e.g. when more schema files are uploaded
+    let current_kind = (*source_kind)
+        .clone()
+        .or_else(|| find_root_kind(&root_kinds, &query));
+    let source_kind_index = root_kinds
+        .iter()
+        .position(|(kind, _)| Some(kind) == current_kind.as_ref())
+        .unwrap_or(0);
+
+    let template = use_state(|| query.template.clone().unwrap_or_default());
+
+    // the template loaded into a new editor, which is recreated whenever `generation` changes
+    let loaded_template = use_state(|| query.template.clone().unwrap_or_default());
+    let generation = use_state(|| 0_usize);
+
+    let open_template = Callback::from({
+        let source_kind = source_kind.clone();
+        let on_plugins_change = props.on_plugins_change.clone();
+        let template = template.clone();
+        let loaded_template = loaded_template.clone();
+        let generation = generation.clone();
+        let root_kinds = root_kinds.clone();
+        move |query: EditorQuery| {
+            source_kind.set(find_root_kind(&root_kinds, &query));
+            on_plugins_change.emit(PluginFilter::from_query(&query));
+            let query_template = query.template.unwrap_or_default();
+            template.set(query_template.clone());
+            loaded_template.set(query_template);
+            generation.set(*generation + 1);
+        }
+    });
+
+    let hidden_plugins: Vec<_> = schema
+        .known_plugins
+        .iter()
+        .filter(|plugin| !plugin_filter.contains(Some(*plugin)))
+        .cloned()
+        .collect();
+    let current_query = EditorQuery {
+        kind: current_kind.as_ref().map(|kind| kind.0.clone()),
+        hide: (!hidden_plugins.is_empty()).then(|| hidden_plugins.join(",")),
+        template: (!template.is_empty()).then(|| (*template).clone()),
+    };
+    use_effect_with_deps(
+        move |query| {
+            if let Err(err) = navigator.replace_with_query(&Route::Editor, query) {
+                log::error!("cannot update URL: {err}");
+            }
+            storage::save_session(query);
+        },
+        current_query.clone(),
+    );
+
+    defy! {
+        if !schema.errors.is_empty() || !schema.conflicts.is_empty() {
+            div(class = "fixed-corner is-pulled-right mx-3 my-3") {
+                components::Modal(button = defy! {
+                    button(class = "button is-borderless") {
+                        span(class = classes!["icon", "is-large", if schema.errors.is_empty() { "has-text-warning" } else { "has-text-danger" }]) {
+                            i(class = "mdi mdi-48px mdi-alert-circle");
+                        }
+                    }
+                }) {
+                    if !schema.errors.is_empty() {
+                        article(class = "message is-danger") {
+                            div(class = "message-header") {
+                                + "Error loading schema for some plugins:";
+                            }
+
+                            for err in &schema.errors {
+                                div(class = "message-body") {
+                                    pre {
+                                        + format!("{err:?}");
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if !schema.conflicts.is_empty() {
+                        article(class = "message is-warning") {
+                            div(class = "message-header") {
+                                + "Conflicting definitions between sources:";
+                            }
+
+                            for conflict in &schema.conflicts {
+                                div(class = "message-body") {
+                                    p {
+                                        strong { + conflict.subject.to_string(); }
+                                    }
+                                    p(class = "is-size-7") {
+                                        + format!("{} is overridden by {}", conflict.old_origin, conflict.new_origin);
+                                    }
+                                    ul {
+                                        for difference in &conflict.differences {
+                                            li { + difference.to_string(); }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        div(class = "section") {
+            div(class = "container") {
+                h1(class = "title") {
+                    + "InfoAPI template editor";
+                }
+
+                div(class = "field") {
+                }
+
+                div(class = "field") {
+                    label(class = "label is-medium") {
+                        + "Which template are you editing?";
+                    }
+
+                    div(class = "level") {
+                        components::EditableSelect<data::KindId>(
+                            key = *generation,
+                            options = root_kinds,
+                            default = source_kind_index,
+                            on_change = Callback::from({
+                                let source_kind = source_kind.clone();
+                                move |kind| source_kind.set(Some(kind))
+                            }),
+                            button_class = "is-medium",
+                            input_class = "is-medium",
+                        );
+
+                        components::Modal(button = defy! {
+                            button(class = "button is-info is-small") {
+                                span(class = "icon") {
+                                    i(class = "mdi mdi-wrench");
+                                }
+                                span { + "Select plugins"; }
+                            }
+                        }) {
+                            div(class = "box panel") {
+                                p(class = "panel-heading") {
+                                    + "Select plugins";
+                                }
+                                div(class = "panel-block") {
+                                    p(class = "is-size-7") {
+                                        + "Uncheck plugins here to hide them from search results.";
+                                    }
+                                }
+
+                                for plugin in &schema.known_plugins {
+                                    a(class = "panel-block", onclick = toggle_plugin(plugin).reform(|_| ())) {
+                                        span(class = "icon") {
+                                            if plugin_filter.contains(Some(plugin)) {
+                                                i(class = "mdi mdi-check");
+                                            } else {
+                                                i(class = "mdi mdi-cancel");
+                                            }
+                                        }
+                                        + plugin.clone();
+                                    }
+                                }
+                            }
+                        }
+
+                        components::Modal(button = defy! {
+                            button(class = "button is-info is-small") {
+                                span(class = "icon") {
+                                    i(class = "mdi mdi-folder");
+                                }
+                                span { + "My templates"; }
+                            }
+                        }) {
+                            components::TemplateLibrary(current = current_query, on_open = open_template);
+                        }
+
+                        components::Modal(button = defy! {
+                            button(class = "button is-info is-small") {
+                                span(class = "icon") {
+                                    i(class = "mdi mdi-upload");
+                                }
+                                span { + "Load schema files"; }
+                            }
+                        }) {
+                            div(class = "box") {
+                                p(class = "block") {
+                                    + "Load schema files exported by plugins that are not published. ";
+                                    + "Errors are listed in the error icon at the corner.";
+                                }
+                                components::SchemaUpload(on_upload = props.on_upload.clone());
+                            }
+                        }
+
+                        components::Modal(button = defy! {
+                            button(class = "button is-info is-small") {
+                                span(class = "icon") {
+                                    i(class = "mdi mdi-stethoscope");
+                                }
+                                span { + "Schema health"; }
+                            }
+                        }) {
+                            components::SchemaHealth(schema = schema.clone());
+                        }
+
+                        components::Modal(button = defy! {
+                            button(class = "button is-info is-small") {
+                                span(class = "icon") {
+                                    i(class = "mdi mdi-export");
+                                }
+                                span { + "Export catalog"; }
+                            }
+                        }) {
+                            components::CatalogExport(schema = schema.clone(), plugins = plugin_filter.clone());
+                        }
+
+                        components::Modal(button = defy! {
+                            button(class = "button is-info is-small") {
+                                span(class = "icon") {
+                                    i(class = "mdi mdi-cog");
+                                }
+                                span { + "Schema sources"; }
+                            }
+                        }) {
+                            components::SourceSettingsForm(
+                                settings = props.source_settings.clone(),
+                                on_save = props.on_source_settings_change.clone(),
+                            );
+
+                            div(class = "box") {
+                                h2(class = "title is-5") {
+                                    + "Loaded schemas";
+                                }
+                                table(class = "table is-fullwidth is-narrow") {
+                                    thead {
+                                        tr {
+                                            th { + "Source"; }
+                                            th { + "Generator"; }
+                                            th { + "InfoAPI"; }
+                                            th { + "Format"; }
+                                        }
+                                    }
+                                    tbody {
+                                        for source in &schema.sources {
+                                            tr {
+                                                td(class = "is-family-monospace") { + &source.origin; }
+                                                td { + source.generator.as_deref().unwrap_or("unknown"); }
+                                                td { + source.infoapi_version.as_deref().unwrap_or("unknown"); }
+                                                td {
+                                                    + format!("v{}", source.format_version);
+                                                    if source.format_version < data::FORMAT_VERSION {
+                                                        span(class = "tag is-warning is-light ml-1", title = "Migrated from an older format") {
+                                                            + "migrated";
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div(class = "field") {
+                    if let Some(source_kind) = &current_kind {
+                        components::TemplateEditor(
+                            key = *generation,
+                            schema = schema.clone(),
+                            source_kind = source_kind.clone(),
+                            plugins = plugin_filter.clone(),
+                            initial_template = (*loaded_template).clone(),
+                            on_template_change = util::set_state(&template),
+                        );
+                    } else {
+                        div(class = "notification is-warning") {
+                            + "The loaded schemas do not define any template kinds. ";
+                            + "Load the schema of a plugin that provides templates to edit them.";
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct EditorProps {
+    schema: data::Data,
+    /// Called with schema files chosen by the user.
+    on_upload: Callback<Vec<components::Upload>>,
+    source_settings: data::SourceSettings,
+    on_source_settings_change: Callback<data::SourceSettings>,
+    /// The plugins shown in search results.
+    plugins: PluginFilter,
+    on_plugins_change: Callback<PluginFilter>,
+}
+
+/// The editor state in the URL, or the last session unless a shared link is opened.
+fn initial_query(location: Option<Location>) -> EditorQuery {
+    let query = location
+        .and_then(|location| location.query::<EditorQuery>().ok())
+        .unwrap_or_default();
+    if query == EditorQuery::default() {
+        storage::load_session().unwrap_or_default()
+    } else {
+        query
+    }
+}
+
+/// The root kind selected in `query`, or the first root kind if it is unknown.
+///
+/// Returns `None` if there are no root kinds, e.g. when only schemas without templates are loaded.
+fn find_root_kind(
+    root_kinds: &[(data::KindId, String)],
+    query: &EditorQuery,
+) -> Option<data::KindId> {
+    let restored = root_kinds
+        .iter()
+        .find(|(kind, _)| Some(&kind.0) == query.kind.as_ref());
+    let (kind, _) = restored.or(root_kinds.first())?;
+    Some(kind.clone())
+}
+
+/// The set of plugins hidden from search results.
+///
+/// Hidden plugins are stored instead of shown plugins
+/// so that plugins loaded later are shown by default.
+#[derive(Debug, Clone)]
+pub struct PluginFilter(Rc<RefCell<HashMap<String, ()>>>);
+impl PartialEq for PluginFilter {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl FromIterator<String> for PluginFilter {
+    fn from_iter<T: IntoIterator<Item = String>>(iter: T) -> Self {
+        let map = iter.into_iter().map(|k| (k, ())).collect();
+        Self(Rc::new(RefCell::new(map)))
+    }
+}
+impl PluginFilter {
+    pub(crate) fn from_query(query: &EditorQuery) -> Self {
+        query.hidden_plugins().map(str::to_string).collect()
+    }
+
+    pub(crate) fn contains<Q: Hash + Eq>(
+        &self,
+        source_plugin: Option<impl ops::Deref<Target = Q>>,
+    ) -> bool
+    where
+        String: borrow::Borrow<Q>,
+    {
+        match &source_plugin {
+            Some(plugin) => !self.0.borrow().contains_key(plugin),
+            None => true,
+        }
+    }
+
+    /// Toggles `plugin` in a copy of the map,
+    /// so that components memoized on the filter see the change.
+    pub(crate) fn toggle(&mut self, plugin: String) {
+        let mut map = self.0.borrow().clone();
+        match map.entry(plugin) {
+            hash_map::Entry::Occupied(entry) => entry.remove(),
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(());
+            }
+        }
+        self.0 = Rc::new(RefCell::new(map));
+    }
+}
+
+fn fallback() -> Html {
+    defy! {
+        section(class = "hero is-fullheight") {
+            div(class = "hero-body") {
+                p(class = "title has-text") {
+                    + "Loading\u{2026}";
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,592 @@
+//! Template syntax tree, ported from `lib/ast.php`.
+
+use std::ops::Range;
+
+use crate::{
+    data::{QualifiedRef, FQN_SEPARATOR},
+    parser::{ParseError, StringParser},
+};
+
+/// The entire template string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub elements: Vec<Element>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    RawText(RawText),
+    Expr(Expr),
+}
+
+/// Raw text that can be printed as-is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawText {
+    /// The parsed string to be printed, e.g. `{` for `{{`.
+    pub parsed: String,
+    /// The original string that contains escape sequences.
+    pub original: String,
+}
+
+/// An expression that may have a coalescence chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    /// The main expression to resolve.
+    pub main: InfoExpr,
+    /// The expression to use if the main expression is null or does not have a display descriptor.
+    pub else_: Option<Box<Expr>>,
+}
+
+/// An expression that resolves info.
+///
+/// Unlike the parent-linked `InfoExpr` in PHP, the calls are stored from left to right,
+/// e.g. `a`, `b`, `c` in `{a b c}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfoExpr {
+    pub calls: Vec<MappingCall>,
+}
+
+/// Calls a mapping with or without arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MappingCall {
+    /// The partially qualified reference to the mapping, e.g. `a:b` in `{ a:b(c, d=e) }`.
+    pub name: QualifiedRef,
+    /// The arguments passed to the mapping, e.g. `c` and `d=e` in `{ a:b(c, d=e) }`.
+    pub args: Option<Vec<Arg>>,
+    /// The byte range of the name in the template.
+    pub name_span: Range<usize>,
+}
+
+/// An argument passed to a mapping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arg {
+    /// Name of the argument if specified, e.g. `d` in `{ a:b(c, d=e) }`.
+    pub name: Option<String>,
+    pub value: ArgValue,
+    /// The byte range of the whole argument in the template.
+    pub span: Range<usize>,
+    /// Characters before an expression value that look like a number but are not one,
+    /// e.g. `e` in `exp`, with their byte range.
+    ///
+    /// The server skips them like `Parse::parseValue`, so `exp` calls `xp`.
+    pub skipped_prefix: Option<(String, Range<usize>)>,
+}
+
+/// The value of an argument.
+///
+/// Parses as an `Expr` if it starts with an identifier (except `true` and `false`),
+/// otherwise parses as one JSON expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgValue {
+    Json(JsonValue),
+    Expr(Expr),
+}
+
+/// A value in JSON format to be interpreted based on the type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonValue {
+    pub as_string: String,
+    pub json: String,
+}
+
+impl JsonValue {
+    fn verbatim(json: &str) -> Self {
+        Self {
+            as_string: json.to_string(),
+            json: json.to_string(),
+        }
+    }
+}
+
+pub fn is_fqn_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+pub fn parse(buf: &str) -> Result<Template, ParseError> {
+    let mut parser = StringParser::new(buf);
+
+    let mut elements = Vec::new();
+    while !parser.eof() {
+        elements.push(parse_element(&mut parser)?);
+    }
+
+    Ok(Template { elements })
+}
+
+pub fn parse_element(parser: &mut StringParser) -> Result<Element, ParseError> {
+    if parser.read_exact_text("{{") {
+        return Ok(Element::RawText(RawText {
+            parsed: "{".into(),
+            original: "{{".into(),
+        }));
+    }
+    if parser.read_exact_text("}}") {
+        return Ok(Element::RawText(RawText {
+            parsed: "}".into(),
+            original: "}}".into(),
+        }));
+    }
+    if parser.peek_starts_with("}") {
+        return Err(parser.error_here("unmatched `}` should be escaped as `}}`"));
+    }
+
+    let start_expr_pos = parser.pos;
+    if parser.read_exact_text("{") {
+        let expr = parse_expr(parser, &["}"])?;
+        parser.skip_whitespace();
+        if !parser.read_exact_text("}") {
+            return Err(parser.span_error(
+                "unclosed `{}` or invalid character in expression",
+                start_expr_pos,
+                parser.pos + 1,
+            ));
+        }
+        return Ok(Element::Expr(expr));
+    }
+
+    let substr = match parser.read_until(&['{', '}']) {
+        Some(substr) => substr,
+        None => parser.read_all(),
+    };
+    Ok(Element::RawText(RawText {
+        parsed: substr.to_string(),
+        original: substr.to_string(),
+    }))
+}
+
+pub fn parse_expr(parser: &mut StringParser, terminators: &[&str]) -> Result<Expr, ParseError> {
+    let main_terminators: Vec<&str> = ["|"].iter().chain(terminators).copied().collect();
+    let main = parse_info_expr(parser, &main_terminators)?;
+    parser.skip_whitespace();
+
+    let mut else_ = None;
+    if parser.read_exact_text("|") {
+        else_ = Some(Box::new(parse_expr(parser, terminators)?));
+    }
+
+    Ok(Expr { main, else_ })
+}
+
+pub fn parse_info_expr(
+    parser: &mut StringParser,
+    terminators: &[&str],
+) -> Result<InfoExpr, ParseError> {
+    let mut calls = Vec::new();
+
+    loop {
+        calls.push(parse_call(parser)?);
+
+        parser.skip_whitespace();
+        if terminators
+            .iter()
+            .any(|terminator| parser.peek_starts_with(terminator))
+        {
+            return Ok(InfoExpr { calls });
+        }
+    }
+}
+
+pub fn parse_call(parser: &mut StringParser) -> Result<MappingCall, ParseError> {
+    parser.skip_whitespace();
+    let start_name_pos = parser.pos;
+    let name = parse_name(parser)?;
+    let name_span = start_name_pos..parser.pos;
+
+    let mut args = None;
+    parser.skip_whitespace();
+    let start_args_pos = parser.pos;
+    if parser.read_exact_text("(") {
+        let args = args.insert(Vec::new());
+        while !parser.read_exact_text(")") {
+            args.push(parse_arg(parser)?);
+            if !parser.read_exact_text(",") {
+                if !parser.read_exact_text(")") {
+                    return Err(parser.span_error(
+                        "multiple arguments must be separated by `,` or terminated with `)`",
+                        start_args_pos,
+                        parser.pos,
+                    ));
+                }
+                break;
+            }
+        }
+    }
+
+    Ok(MappingCall {
+        name,
+        args,
+        name_span,
+    })
+}
+
+pub fn parse_name(parser: &mut StringParser) -> Result<QualifiedRef, ParseError> {
+    let mut tokens = Vec::new();
+    parser.skip_whitespace();
+
+    loop {
+        let token = parser.read_charset(is_fqn_token_char);
+        if token.is_empty() {
+            return Err(parser.error_here("expected mapping name"));
+        }
+        tokens.push(token.to_string());
+
+        if !parser.read_exact_text(FQN_SEPARATOR) {
+            break;
+        }
+    }
+
+    Ok(QualifiedRef(tokens))
+}
+
+pub fn parse_arg(parser: &mut StringParser) -> Result<Arg, ParseError> {
+    parser.skip_whitespace();
+    let start_pos = parser.pos;
+
+    for literal in ["true", "false"] {
+        if parser.read_exact_text(literal) {
+            return Ok(Arg {
+                name: None,
+                value: ArgValue::Json(JsonValue::verbatim(literal)),
+                span: start_pos..parser.pos,
+                skipped_prefix: None,
+            });
+        }
+    }
+
+    let mut arg_name = None;
+    parser.try_with(|parser| {
+        let name_token = parser.read_charset(is_fqn_token_char);
+        parser.skip_whitespace();
+
+        if parser.read_exact_text("=") {
+            arg_name = Some(name_token.to_string());
+            return true;
+        }
+        false
+    });
+
+    parser.skip_whitespace();
+    let value_pos = parser.pos;
+    let rest = parser.rest();
+    let number_like = &rest[..rest.find(|c| !is_number_char(c)).unwrap_or(rest.len())];
+    let value = parse_value(parser)?;
+    let skipped_prefix = match value {
+        ArgValue::Expr(_) if !number_like.is_empty() => Some((
+            number_like.to_string(),
+            value_pos..value_pos + number_like.len(),
+        )),
+        _ => None,
+    };
+
+    Ok(Arg {
+        name: arg_name,
+        value,
+        span: start_pos..parser.pos,
+        skipped_prefix,
+    })
+}
+
+/// Characters read by `Parse::parseValue` as a number before checking whether they form one.
+fn is_number_char(c: char) -> bool {
+    c.is_ascii_digit() || "e.-+".contains(c)
+}
+
+pub fn parse_value(parser: &mut StringParser) -> Result<ArgValue, ParseError> {
+    parser.skip_whitespace();
+    let start_pos = parser.pos;
+
+    for literal in ["true", "false"] {
+        if parser.read_exact_text(literal) {
+            return Ok(ArgValue::Json(JsonValue::verbatim(literal)));
+        }
+    }
+
+    // like the server, a prefix that is not a number stays consumed, so `exp` is parsed as `xp`
+    let num = parser.read_charset(is_number_char);
+    if !num.is_empty() && num.parse::<f64>().is_ok() {
+        return Ok(ArgValue::Json(JsonValue::verbatim(num)));
+    }
+
+    if parser.peek_starts_with("\"") {
+        let string = read_json_string(parser);
+        if string.is_empty() {
+            return Err(parser.error_here("expected JSON string"));
+        }
+
+        return match serde_json::from_str::<String>(string) {
+            Ok(parsed) => Ok(ArgValue::Json(JsonValue {
+                as_string: parsed,
+                json: string.to_string(),
+            })),
+            Err(err) => Err(parser.span_error(
+                format!("JSON parse error: {err}"),
+                start_pos,
+                start_pos + string.len(),
+            )),
+        };
+    }
+
+    Ok(ArgValue::Expr(parse_expr(parser, &[",", ")"])?))
+}
+
+/// Reads a double-quoted JSON string literal including the quotes,
+/// or returns an empty string without consuming anything if the literal is unterminated.
+fn read_json_string<'t>(parser: &mut StringParser<'t>) -> &'t str {
+    let rest = parser.rest();
+    let mut chars = rest.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                parser.pos += i + 1;
+                return &rest[..=i];
+            }
+            '\\' => _ = chars.next(),
+            '\0'..='\x1f' | '\x7f' => break,
+            _ => {}
+        }
+    }
+
+    ""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(value: &ArgValue) -> &JsonValue {
+        match value {
+            ArgValue::Json(value) => value,
+            ArgValue::Expr(expr) => panic!("expected JSON, got {expr:?}"),
+        }
+    }
+
+    fn expr(value: &ArgValue) -> &Expr {
+        match value {
+            ArgValue::Expr(expr) => expr,
+            ArgValue::Json(value) => panic!("expected expression, got {value:?}"),
+        }
+    }
+
+    fn names(expr: &InfoExpr) -> Vec<String> {
+        expr.calls
+            .iter()
+            .map(|call| call.name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parse_named_bool_args() {
+        for (input, literal, rest) in [
+            ("arg=true, xxx", "true", ", xxx"),
+            ("arg=false) xxx", "false", ") xxx"),
+        ] {
+            let mut parser = StringParser::new(input);
+            let arg = parse_arg(&mut parser).unwrap();
+            assert_eq!(arg.name.as_deref(), Some("arg"));
+            assert_eq!(json(&arg.value).as_string, literal);
+            assert_eq!(parser.rest(), rest);
+        }
+    }
+
+    #[test]
+    fn parse_unnamed_bool_args() {
+        for (input, literal, rest) in [
+            ("true) xxx", "true", ") xxx"),
+            ("false, xxx", "false", ", xxx"),
+        ] {
+            let mut parser = StringParser::new(input);
+            let arg = parse_arg(&mut parser).unwrap();
+            assert_eq!(arg.name, None);
+            assert_eq!(json(&arg.value).as_string, literal);
+            assert_eq!(parser.rest(), rest);
+        }
+    }
+
+    #[test]
+    fn parse_unnamed_numeric_args() {
+        // `123` must not be treated as an argument name
+        for (input, number) in [("-1.23e+45, xxx", "-1.23e+45"), ("123, xxx", "123")] {
+            let mut parser = StringParser::new(input);
+            let arg = parse_arg(&mut parser).unwrap();
+            assert_eq!(arg.name, None);
+            assert_eq!(json(&arg.value).as_string, number);
+            assert_eq!(arg.skipped_prefix, None);
+            assert_eq!(parser.rest(), ", xxx");
+        }
+    }
+
+    #[test]
+    fn parse_unnamed_string_arg() {
+        let input = r#""a\"b\n\\", xxx"#;
+        let mut parser = StringParser::new(input);
+        let arg = parse_arg(&mut parser).unwrap();
+        assert_eq!(arg.name, None);
+        let value = json(&arg.value);
+        assert_eq!(value.as_string, "a\"b\n\\");
+        assert_eq!(value.json, input.split(',').next().unwrap());
+        assert_eq!(parser.rest(), ", xxx");
+    }
+
+    #[test]
+    fn parse_call_without_args() {
+        let mut parser = StringParser::new("foo:bar qux");
+        let call = parse_call(&mut parser).unwrap();
+        assert_eq!(call.name, QualifiedRef::parse("foo:bar"));
+        assert_eq!(call.args, None);
+        assert_eq!(call.name_span, 0..7);
+        assert_eq!(parser.rest().trim_start(), "qux");
+    }
+
+    #[test]
+    fn parse_call_with_empty_args() {
+        let mut parser = StringParser::new("foo:bar() qux");
+        let call = parse_call(&mut parser).unwrap();
+        assert_eq!(call.name, QualifiedRef::parse("foo:bar"));
+        assert_eq!(call.args, Some(Vec::new()));
+        assert_eq!(parser.rest().trim_start(), "qux");
+    }
+
+    #[test]
+    fn parse_call_with_named_args() {
+        let mut parser = StringParser::new(
+            r#"foo:bar(corge = true, grault = 123e1, baz = "text", waldo = false) qux"#,
+        );
+        let call = parse_call(&mut parser).unwrap();
+        let args = call.args.unwrap();
+        let parsed: Vec<_> = args
+            .iter()
+            .map(|arg| {
+                (
+                    arg.name.as_deref().unwrap(),
+                    json(&arg.value).as_string.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                ("corge", "true"),
+                ("grault", "123e1"),
+                ("baz", "text"),
+                ("waldo", "false")
+            ]
+        );
+        assert_eq!(parser.rest().trim_start(), "qux");
+    }
+
+    #[test]
+    fn parse_call_with_unnamed_args() {
+        let mut parser = StringParser::new(r#"foo:bar(true, 123e1, "text", false) qux"#);
+        let call = parse_call(&mut parser).unwrap();
+        let args = call.args.unwrap();
+        assert!(args.iter().all(|arg| arg.name.is_none()));
+        let jsons: Vec<_> = args
+            .iter()
+            .map(|arg| json(&arg.value).json.as_str())
+            .collect();
+        assert_eq!(jsons, ["true", "123e1", "\"text\"", "false"]);
+        assert_eq!(args[1].span, 14..19);
+        assert_eq!(parser.rest().trim_start(), "qux");
+    }
+
+    #[test]
+    fn parse_info_expr_until_terminator() {
+        for input in ["foo:bar qux |", "foo:bar() qux}"] {
+            let mut parser = StringParser::new(input);
+            let expr = parse_info_expr(&mut parser, &["|", "}"]).unwrap();
+            assert_eq!(names(&expr), ["foo:bar", "qux"]);
+        }
+    }
+
+    #[test]
+    fn parse_expr_with_else_branch() {
+        let mut parser = StringParser::new("foo:bar() qux | corge grault() }");
+        let expr = parse_expr(&mut parser, &["}"]).unwrap();
+        assert_eq!(names(&expr.main), ["foo:bar", "qux"]);
+        let else_ = expr.else_.unwrap();
+        assert_eq!(names(&else_.main), ["corge", "grault"]);
+        assert_eq!(else_.else_, None);
+    }
+
+    #[test]
+    fn parse_nested_expr_args() {
+        let template = parse("{a(b c(d = 1) | e, f = g) h}").unwrap();
+        let [Element::Expr(outer)] = &template.elements[..] else {
+            panic!("expected one expression");
+        };
+        assert_eq!(names(&outer.main), ["a", "h"]);
+
+        let args = outer.main.calls[0].args.as_ref().unwrap();
+        let first = expr(&args[0].value);
+        assert_eq!(names(&first.main), ["b", "c"]);
+        let inner = first.main.calls[1].args.as_ref().unwrap();
+        assert_eq!(inner[0].name.as_deref(), Some("d"));
+        assert_eq!(json(&inner[0].value).json, "1");
+        assert_eq!(names(&first.else_.as_ref().unwrap().main), ["e"]);
+
+        assert_eq!(args[1].name.as_deref(), Some("f"));
+        assert_eq!(names(&expr(&args[1].value).main), ["g"]);
+    }
+
+    #[test]
+    fn parse_escaped_braces() {
+        let template = parse("a {{b}} {c}}}").unwrap();
+        let texts: Vec<_> = template
+            .elements
+            .iter()
+            .map(|element| match element {
+                Element::RawText(text) => (text.parsed.as_str(), text.original.as_str()),
+                Element::Expr(_) => ("<expr>", ""),
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                ("a ", "a "),
+                ("{", "{{"),
+                ("b", "b"),
+                ("}", "}}"),
+                (" ", " "),
+                ("<expr>", ""),
+                ("}", "}}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_number_like_prefix() {
+        // `Parse::parseValue` consumes `e` as a number before parsing `xp` as an expression
+        let mut parser = StringParser::new("f(exp)");
+        let call = parse_call(&mut parser).unwrap();
+        let arg = &call.args.unwrap()[0];
+        assert_eq!(names(&expr(&arg.value).main), ["xp"]);
+        assert_eq!(arg.skipped_prefix, Some(("e".to_string(), 2..3)));
+
+        let mut parser = StringParser::new("f(x = 1-2 y)");
+        let call = parse_call(&mut parser).unwrap();
+        let arg = &call.args.unwrap()[0];
+        assert_eq!(names(&expr(&arg.value).main), ["y"]);
+        assert_eq!(arg.skipped_prefix, Some(("1-2".to_string(), 6..9)));
+    }
+
+    #[test]
+    fn error_spans() {
+        for (input, why, span) in [
+            ("a } b", "unmatched `}` should be escaped as `}}`", 2..3),
+            // the call chain only ends at a terminator, so a missing `}` runs into the end of input
+            ("{a b", "expected mapping name", 4..4),
+            ("{}", "expected mapping name", 1..2),
+            (
+                "{a(1 2)}",
+                "multiple arguments must be separated by `,` or terminated with `)`",
+                2..4,
+            ),
+            (r#"{a("\x")}"#, "JSON parse error", 3..7),
+        ] {
+            let err = parse(input).unwrap_err();
+            assert!(err.why.starts_with(why), "{input}: {}", err.why);
+            assert_eq!(err.start..err.end, span, "{input}");
+        }
+    }
+}
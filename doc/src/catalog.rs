@@ -0,0 +1,263 @@
+//! Exports the mappings in the schema as a list of available placeholders,
+//! e.g. for plugin authors to paste into their READMEs.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::data::{All, KindId, MappingDef, ParamDef};
+
+/// Plugins are unknown for mappings from schemas that do not record the source plugin.
+const UNKNOWN_PLUGIN: &str = "Other";
+
+/// A flattened entry of the JSON catalog.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry<'t> {
+    pub source_plugin: Option<&'t str>,
+    pub source_kind: &'t str,
+    /// The shortest name that can be written in a template.
+    pub minified_name: String,
+    pub name: &'t str,
+    pub parameters: Vec<Param<'t>>,
+    pub target_kind: &'t str,
+    pub help: &'t str,
+    pub mutable: bool,
+    pub is_implicit: bool,
+    pub alias_of: Option<&'t str>,
+    #[serde(skip)]
+    mapping: &'t MappingDef,
+}
+
+#[derive(Serialize)]
+pub struct Param<'t> {
+    pub name: &'t str,
+    pub kind: &'t str,
+    pub optional: bool,
+    pub multi: bool,
+}
+
+/// Lists the mappings accepted by `shown`, ordered by source plugin, source kind and name.
+pub fn entries<'t>(schema: &'t All, shown: impl Fn(&MappingDef) -> bool) -> Vec<Entry<'t>> {
+    let mut entries: Vec<_> = schema
+        .mappings
+        .values()
+        .flat_map(|mappings| {
+            mappings
+                .values()
+                .filter(|mapping| shown(mapping))
+                .map(|mapping| Entry {
+                    source_plugin: mapping.metadata.source_plugin.as_deref(),
+                    source_kind: &mapping.source_kind.0,
+                    minified_name: mapping.name.minify(mappings.keys()),
+                    name: &mapping.name.0,
+                    parameters: mapping
+                        .parameters
+                        .iter()
+                        .map(|param| Param {
+                            name: &param.name.0,
+                            kind: &param.kind.0,
+                            optional: param.optional,
+                            multi: param.multi,
+                        })
+                        .collect(),
+                    target_kind: &mapping.target_kind.0,
+                    help: &mapping.help,
+                    mutable: mapping.mutable,
+                    is_implicit: mapping.is_implicit,
+                    alias_of: mapping.metadata.alias_of.as_deref(),
+                    mapping,
+                })
+        })
+        .collect();
+    entries.sort_by_key(|entry| (entry.source_plugin, entry.source_kind, entry.name));
+    entries
+}
+
+pub fn json(schema: &All, shown: impl Fn(&MappingDef) -> bool) -> String {
+    serde_json::to_string_pretty(&entries(schema, shown)).expect("catalog is serializable")
+}
+
+/// Formats the catalog as Markdown with a section per source plugin and a table per source kind.
+///
+/// Aliases are listed with their canonical mapping instead of in separate rows.
+pub fn markdown(schema: &All, shown: impl Fn(&MappingDef) -> bool) -> String {
+    let entries = entries(schema, shown);
+
+    let mut aliases = BTreeMap::<(&str, &str), Vec<&str>>::new();
+    for entry in &entries {
+        if let Some(alias_of) = entry.alias_of {
+            aliases
+                .entry((entry.source_kind, alias_of))
+                .or_default()
+                .push(&entry.minified_name);
+        }
+    }
+
+    let mut groups = BTreeMap::<&str, BTreeMap<&str, Vec<&Entry>>>::new();
+    for entry in entries.iter().filter(|entry| entry.alias_of.is_none()) {
+        groups
+            .entry(entry.source_plugin.unwrap_or(UNKNOWN_PLUGIN))
+            .or_default()
+            .entry(entry.source_kind)
+            .or_default()
+            .push(entry);
+    }
+
+    let mut out = String::from("# Available placeholders\n");
+    for (plugin, kinds) in groups {
+        out += &format!("\n## {plugin}\n");
+        for (kind, entries) in kinds {
+            out += &format!("\n### From `{kind}`\n\n");
+            if let Some(help) = schema
+                .kinds
+                .get(&KindId(kind.into()))
+                .map(|def| &def.help)
+                .filter(|help| !help.is_empty())
+            {
+                out += &format!("{}\n\n", table_cell(help));
+            }
+
+            out += "| Name | Full name | Parameters | Result | Description | Updates live |\n";
+            out += "| --- | --- | --- | --- | --- | --- |\n";
+            for entry in entries {
+                let mut names = format!("`{}`", entry.minified_name);
+                for alias in aliases.get(&(kind, entry.name)).into_iter().flatten() {
+                    names += &format!(", `{alias}`");
+                }
+                if entry.is_implicit {
+                    names += " (implicit)";
+                }
+
+                out += &format!(
+                    "| {names} | `{}` | {} | `{}` | {} | {} |\n",
+                    entry.name,
+                    params_cell(&entry.mapping.parameters),
+                    entry.target_kind,
+                    table_cell(entry.help),
+                    if entry.mutable { "Yes" } else { "No" },
+                );
+            }
+        }
+    }
+    out
+}
+
+fn params_cell(params: &[ParamDef]) -> String {
+    if params.is_empty() {
+        return "-".into();
+    }
+
+    let params: Vec<_> = params.iter().map(|param| format!("`{param}`")).collect();
+    params.join(", ")
+}
+
+/// Keeps text on one line and escapes the column separator.
+fn table_cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{implicit, mapping, param, schema, with_params};
+
+    const ROOT: &str = "test/root";
+
+    fn fixture() -> All {
+        let plugin = |mut mapping: serde_json::Value| {
+            mapping["metadata"]["infoapi/source-plugin"] = "Test".into();
+            mapping
+        };
+
+        let mut name = plugin(mapping(ROOT, "test:name", KindId::STRING));
+        name["mutable"] = true.into();
+        let mut nick = plugin(mapping(ROOT, "test:nick", KindId::STRING));
+        nick["metadata"]["infoapi/alias-of"] = "test:name".into();
+        let mut greet = plugin(with_params(
+            mapping(ROOT, "test:greet", KindId::STRING),
+            vec![
+                param("target", KindId::STRING, false, false),
+                param("extra", KindId::STRING, true, true),
+            ],
+        ));
+        greet["help"] = "Greets a | b\nin one line".into();
+
+        schema(
+            &[(ROOT, false), (KindId::STRING, true)],
+            vec![
+                name,
+                nick,
+                greet,
+                implicit(ROOT, "other:string", KindId::STRING),
+            ],
+        )
+    }
+
+    #[test]
+    fn markdown_tables() {
+        let expected = "\
+# Available placeholders
+
+## Other
+
+### From `test/root`
+
+test/root
+
+| Name | Full name | Parameters | Result | Description | Updates live |
+| --- | --- | --- | --- | --- | --- |
+| `string` (implicit) | `other:string` | - | `infoapi/string` | other:string | No |
+
+## Test
+
+### From `test/root`
+
+test/root
+
+| Name | Full name | Parameters | Result | Description | Updates live |
+| --- | --- | --- | --- | --- | --- |
+| `greet` | `test:greet` | `target: infoapi/string`, `extra?: infoapi/string...` | `infoapi/string` | Greets a \\| b in one line | No |
+| `name`, `nick` | `test:name` | - | `infoapi/string` | test:name | Yes |
+";
+        assert_eq!(markdown(&fixture(), |_| true), expected);
+    }
+
+    #[test]
+    fn json_entries() {
+        let shown = |mapping: &MappingDef| mapping.metadata.source_plugin.is_some();
+        let entries: serde_json::Value = serde_json::from_str(&json(&fixture(), shown)).unwrap();
+
+        let names: Vec<_> = entries
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| {
+                (
+                    entry["minifiedName"].as_str().unwrap(),
+                    entry["name"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("greet", "test:greet"),
+                ("name", "test:name"),
+                ("nick", "test:nick")
+            ]
+        );
+
+        assert_eq!(
+            entries[0]["parameters"][1],
+            serde_json::json!({"name": "extra", "kind": KindId::STRING, "optional": true, "multi": true})
+        );
+        assert_eq!(entries[1]["mutable"], true);
+        assert_eq!(entries[2]["aliasOf"], "test:name");
+        assert_eq!(entries[2]["sourcePlugin"], "Test");
+        assert!(entries[2].get("mapping").is_none());
+    }
+}
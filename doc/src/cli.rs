@@ -0,0 +1,139 @@
+//! Command line argument splitting shared by the native tools.
+
+use anyhow::Context as _;
+
+pub enum Arg {
+    /// An option starting with `-`, without the inline value.
+    Flag(String),
+    Positional(String),
+}
+
+/// Splits arguments into options and positional arguments.
+///
+/// Option values may be passed as either `--option value` or `--option=value`,
+/// and all arguments after `--` are positional.
+pub struct Args<I> {
+    args: I,
+    flag: String,
+    inline_value: Option<String>,
+    positional_only: bool,
+}
+
+impl<I: Iterator<Item = String>> Args<I> {
+    pub fn new(args: I) -> Self {
+        Self {
+            args,
+            flag: String::new(),
+            inline_value: None,
+            positional_only: false,
+        }
+    }
+
+    /// Returns the next argument,
+    /// or an error if the previous option was given an inline value that it does not take.
+    pub fn next(&mut self) -> anyhow::Result<Option<Arg>> {
+        if self.inline_value.take().is_some() {
+            anyhow::bail!("{} does not take a value", self.flag);
+        }
+
+        loop {
+            let Some(arg) = self.args.next() else {
+                return Ok(None);
+            };
+
+            if self.positional_only || !arg.starts_with('-') || arg == "-" {
+                return Ok(Some(Arg::Positional(arg)));
+            }
+            if arg == "--" {
+                self.positional_only = true;
+                continue;
+            }
+
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            self.flag.clone_from(&flag);
+            self.inline_value = inline_value;
+            return Ok(Some(Arg::Flag(flag)));
+        }
+    }
+
+    /// Takes the value of the option last returned by `next`.
+    pub fn value(&mut self) -> anyhow::Result<String> {
+        self.inline_value
+            .take()
+            .or_else(|| self.args.next())
+            .with_context(|| format!("{} requires a value", self.flag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits `args`, describing each as `flag` or `positional` with the values taken by flags.
+    fn split(args: &[&str], takes_value: &[&str]) -> anyhow::Result<Vec<String>> {
+        let mut args = Args::new(args.iter().map(|arg| arg.to_string()));
+        let mut out = Vec::new();
+        while let Some(arg) = args.next()? {
+            match arg {
+                Arg::Flag(flag) if takes_value.contains(&flag.as_str()) => {
+                    out.push(format!("flag {flag} = {}", args.value()?));
+                }
+                Arg::Flag(flag) => out.push(format!("flag {flag}")),
+                Arg::Positional(arg) => out.push(format!("positional {arg}")),
+            }
+        }
+        Ok(out)
+    }
+
+    #[test]
+    fn flags_and_positionals() {
+        let out = split(
+            &["a", "-s", "x", "--schema=y=z", "-", "--quiet", "-k=v"],
+            &["-s", "--schema"],
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            [
+                "positional a",
+                "flag -s = x",
+                "flag --schema = y=z",
+                "positional -",
+                "flag --quiet",
+                // only long options take inline values
+                "flag -k=v",
+            ]
+        );
+    }
+
+    #[test]
+    fn positional_after_separator() {
+        let out = split(&["--quiet", "--", "--quiet", "--", "-s"], &[]).unwrap();
+        assert_eq!(
+            out,
+            [
+                "flag --quiet",
+                "positional --quiet",
+                "positional --",
+                "positional -s"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_value() {
+        let err = split(&["--schema"], &["--schema"]).unwrap_err();
+        assert_eq!(err.to_string(), "--schema requires a value");
+    }
+
+    #[test]
+    fn unexpected_inline_value() {
+        let err = split(&["--quiet=yes", "a"], &[]).unwrap_err();
+        assert_eq!(err.to_string(), "--quiet does not take a value");
+    }
+}
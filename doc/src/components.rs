@@ -1,5 +1,40 @@
+mod args;
+
+mod diagnostics;
+
 mod expr;
-pub use expr::Expression;
+
+mod route_search;
+
+mod template;
+pub use template::TemplateEditor;
+
+mod library;
+pub use library::TemplateLibrary;
+
+mod upload;
+pub use upload::{SchemaUpload, Upload};
+
+mod settings;
+pub use settings::SourceSettingsForm;
+
+mod health;
+pub use health::SchemaHealth;
+
+mod kinds;
+pub use kinds::{KindList, KindPage};
+
+mod mappings;
+pub use mappings::MappingPage;
+
+mod graph;
+pub use graph::SchemaGraph;
+
+mod diff;
+pub use diff::SchemaDiff;
+
+mod export;
+pub use export::CatalogExport;
 
 mod modal;
 pub use modal::Modal;
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use defy::defy;
+use yew::prelude::*;
+
+use super::expr::CoalesceBuilder;
+use crate::{
+    data::{Data, KindId, MappingDef, ParamDef},
+    document::{validate_literal, ArgValue},
+    PluginFilter,
+};
+
+/// Edits the arguments for each parameter of a mapping.
+#[function_component]
+pub fn ArgsForm(props: &ArgsFormProps) -> Html {
+    defy! {
+        for (i, (param, value)) in props.mapping.0.parameters.iter().zip(&props.args).enumerate() {
+            div(class = "field") {
+                label(class = "label") {
+                    + param.name.0.clone();
+                    span(class = "tag is-light ml-2") { + param.kind.0.clone(); }
+                    if param.optional {
+                        span(class = "tag is-light ml-2") { + "optional"; }
+                    }
+                    if param.multi {
+                        span(class = "tag is-light ml-2", title = "The server only uses one value for now") { + "multi"; }
+                    }
+                }
+
+                if let Some(value) = value {
+                    div(class = "field has-addons") {
+                        div(class = "control is-expanded") {
+                            ArgInput(
+                                schema = props.schema.clone(),
+                                root_kind = props.root_kind.clone(),
+                                plugins = props.plugins.clone(),
+                                param = ParamRef(props.mapping.clone(), i),
+                                value = value.clone(),
+                                on_change = Callback::from({
+                                    let args = props.args.clone();
+                                    let on_change = props.on_change.clone();
+                                    move |value| {
+                                        let mut args = args.clone();
+                                        args[i] = Some(value);
+                                        on_change.emit(args);
+                                    }
+                                }),
+                            );
+                        }
+
+                        if param.optional {
+                            div(class = "control") {
+                                button(class = "button is-danger is-light", onclick = update_args(props, move |args| {
+                                    args[i] = None;
+                                })) {
+                                    span(class = "icon") {
+                                        i(class = "mdi mdi-close");
+                                    }
+                                }
+                            }
+                        }
+                    }
+                } else {
+                    button(class = "button is-small", onclick = update_args(props, {
+                        let kind = param.kind.clone();
+                        move |args| args[i] = Some(ArgValue::default_for(&kind))
+                    })) {
+                        span(class = "icon") {
+                            i(class = "mdi mdi-plus");
+                        }
+                        span {
+                            + format!("Add {}", param.name.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn update_args(
+    props: &ArgsFormProps,
+    f: impl Fn(&mut Vec<Option<ArgValue>>) + 'static,
+) -> Callback<MouseEvent> {
+    let args = props.args.clone();
+    let on_change = props.on_change.clone();
+    Callback::from(move |_| {
+        let mut args = args.clone();
+        f(&mut args);
+        on_change.emit(args);
+    })
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ArgsFormProps {
+    pub schema: Data,
+    pub root_kind: KindId,
+    pub plugins: PluginFilter,
+    pub mapping: MappingRef,
+    pub args: Vec<Option<ArgValue>>,
+    pub on_change: Callback<Vec<Option<ArgValue>>>,
+}
+
+/// A shared mapping compared by identity.
+#[derive(Clone)]
+pub struct MappingRef(pub Arc<MappingDef>);
+
+impl PartialEq for MappingRef {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Refers to the parameter at the index of the mapping.
+#[derive(Clone, PartialEq)]
+struct ParamRef(MappingRef, usize);
+
+impl ParamRef {
+    fn get(&self) -> &ParamDef {
+        &self.0 .0.parameters[self.1]
+    }
+}
+
+#[function_component]
+fn ArgInput(props: &ArgInputProps) -> Html {
+    let param = props.param.get();
+
+    match &props.value {
+        ArgValue::Literal(json) => {
+            let error = validate_literal(json).err();
+            let oninput = props.on_change.reform(|event: InputEvent| {
+                let input: web_sys::HtmlInputElement = event.target_unchecked_into();
+                ArgValue::Literal(input.value())
+            });
+
+            defy! {
+                input(
+                    class = classes!["input", error.is_some().then_some("is-danger")],
+                    type = "text",
+                    value = json.clone(),
+                    oninput = oninput,
+                );
+                if let Some(error) = error {
+                    p(class = "help is-danger") { + error; }
+                }
+            }
+        }
+        ArgValue::Expr(chain) => defy! {
+            div(class = "box") {
+                CoalesceBuilder(
+                    schema = props.schema.clone(),
+                    root_kind = props.root_kind.clone(),
+                    target_kind = Some(param.kind.clone()),
+                    plugins = props.plugins.clone(),
+                    chain = chain.clone(),
+                    on_change = props.on_change.reform(ArgValue::Expr),
+                );
+            }
+        },
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct ArgInputProps {
+    schema: Data,
+    root_kind: KindId,
+    plugins: PluginFilter,
+    param: ParamRef,
+    value: ArgValue,
+    on_change: Callback<ArgValue>,
+}
@@ -0,0 +1,71 @@
+use defy::defy;
+use yew::prelude::*;
+
+use crate::diagnostics::{Diagnostic, Severity};
+
+/// Shows the template with problematic spans highlighted, followed by the diagnostic messages.
+#[function_component]
+pub fn DiagnosticList(props: &Props) -> Html {
+    if props.diagnostics.is_empty() {
+        return Html::default();
+    }
+
+    let mut spans: Vec<_> = props.diagnostics.iter().collect();
+    spans.sort_by_key(|diagnostic| (diagnostic.span.start, diagnostic.span.end));
+
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    for diagnostic in spans {
+        if diagnostic.span.start < pos {
+            // overlaps with the previous highlight
+            continue;
+        }
+        segments.push((None, &props.buf[pos..diagnostic.span.start]));
+        segments.push((Some(diagnostic), &props.buf[diagnostic.span.clone()]));
+        pos = diagnostic.span.end;
+    }
+    segments.push((None, &props.buf[pos..]));
+
+    defy! {
+        pre(class = "py-2") {
+            for (diagnostic, text) in segments {
+                match diagnostic {
+                    Some(diagnostic) => {
+                        mark(class = severity_class(diagnostic.severity), title = diagnostic.message.clone()) {
+                            if text.is_empty() {
+                                + "\u{2038}";
+                            } else {
+                                + text;
+                            }
+                        }
+                    }
+                    None => {
+                        + text;
+                    }
+                }
+            }
+        }
+
+        for diagnostic in &props.diagnostics {
+            pre(class = classes!["help", match diagnostic.severity {
+                Severity::Error => "is-danger",
+                Severity::Warning => "is-warning",
+            }]) {
+                + diagnostic.display(&props.buf).to_string();
+            }
+        }
+    }
+}
+
+fn severity_class(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "has-background-danger-light",
+        Severity::Warning => "has-background-warning-light",
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub buf: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
@@ -0,0 +1,192 @@
+use anyhow::Context as _;
+use defy::defy;
+use yew::prelude::*;
+
+use super::{SchemaUpload, Upload};
+use crate::{
+    data::{self, All, Data},
+    diff::{self, Change},
+};
+
+/// Compares schema files of an old version against newer files or the loaded schema,
+/// listing the changes that break existing templates first.
+#[function_component]
+pub fn SchemaDiff(props: &Props) -> Html {
+    let old_uploads = use_state(Vec::<Upload>::new);
+    let new_uploads = use_state(Vec::<Upload>::new);
+
+    let old = use_memo(|uploads| load(uploads), (*old_uploads).clone());
+    let new_files = use_memo(|uploads| load(uploads), (*new_uploads).clone());
+    // the loaded schema is the newest version unless other files are chosen
+    let new = if new_uploads.is_empty() {
+        props.schema.clone()
+    } else {
+        (*new_files).clone()
+    };
+
+    let changes = use_memo(
+        |(old, new)| {
+            let (breaking, compatible): (Vec<_>, Vec<_>) = diff::diff(old, new)
+                .into_iter()
+                .partition(Change::is_breaking);
+            (breaking, compatible)
+        },
+        ((*old).clone(), new.clone()),
+    );
+    let (breaking, compatible) = &*changes;
+
+    defy! {
+        div(class = "columns") {
+            div(class = "column") {
+                UploadBox(
+                    title = "Old version",
+                    hint = "Schema files exported by the version that existing templates were written for.",
+                    uploads = old_uploads.clone(),
+                    schema = (*old).clone(),
+                );
+            }
+            div(class = "column") {
+                UploadBox(
+                    title = "New version",
+                    hint = "Schema files exported by the new version, or the currently loaded schemas if none are chosen.",
+                    uploads = new_uploads.clone(),
+                    schema = (*new_files).clone(),
+                );
+            }
+        }
+
+        if old_uploads.is_empty() {
+            div(class = "notification") {
+                + "Choose the schema files of the old version to compare.";
+            }
+        } else {
+            div(class = "box panel") {
+                p(class = "panel-heading") {
+                    + format!("Breaking changes ({})", breaking.len());
+                }
+
+                if breaking.is_empty() {
+                    div(class = "panel-block") {
+                        span(class = "icon has-text-success") {
+                            i(class = "mdi mdi-check-circle");
+                        }
+                        + "Templates written for the old version still work with the new version.";
+                    }
+                }
+                for change in breaking {
+                    ChangeBlock(change = change.clone(), breaking = true);
+                }
+            }
+
+            div(class = "box panel") {
+                p(class = "panel-heading") {
+                    + format!("Compatible changes ({})", compatible.len());
+                }
+
+                for change in compatible {
+                    ChangeBlock(change = change.clone(), breaking = false);
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub schema: Data,
+}
+
+fn load(uploads: &[Upload]) -> Data {
+    let mut all = All::default();
+    all.extend(uploads.iter().map(|upload| {
+        let contents = upload.contents.clone().map_err(anyhow::Error::msg);
+        contents
+            .and_then(|contents| data::parse_source(&contents, &upload.file_name))
+            .with_context(|| format!("load schema from file {}", upload.file_name))
+    }));
+    Data::from(all)
+}
+
+#[function_component]
+fn UploadBox(props: &UploadBoxProps) -> Html {
+    let on_upload = Callback::from({
+        let uploads = props.uploads.clone();
+        move |new_uploads: Vec<Upload>| {
+            let mut uploads_value = (*uploads).clone();
+            uploads_value.extend(new_uploads);
+            uploads.set(uploads_value);
+        }
+    });
+    let clear = Callback::from({
+        let uploads = props.uploads.clone();
+        move |_| uploads.set(Vec::new())
+    });
+
+    defy! {
+        div(class = "box") {
+            h2(class = "title is-5") {
+                + props.title;
+            }
+            p(class = "block") {
+                + props.hint;
+            }
+
+            div(class = "block") {
+                SchemaUpload(on_upload = on_upload);
+            }
+
+            if !props.uploads.is_empty() {
+                div(class = "field is-grouped is-grouped-multiline") {
+                    for upload in props.uploads.iter() {
+                        div(class = "control") {
+                            span(class = "tag is-family-monospace") { + &upload.file_name; }
+                        }
+                    }
+                    div(class = "control") {
+                        button(class = "button is-small is-light", onclick = clear) {
+                            + "Clear";
+                        }
+                    }
+                }
+            }
+
+            for err in &props.schema.errors {
+                article(class = "message is-danger is-small") {
+                    div(class = "message-body") {
+                        + format!("{err:#}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct UploadBoxProps {
+    title: &'static str,
+    hint: &'static str,
+    uploads: UseStateHandle<Vec<Upload>>,
+    /// The schema loaded from `uploads`, to show the files that could not be loaded.
+    schema: Data,
+}
+
+#[function_component]
+fn ChangeBlock(props: &ChangeBlockProps) -> Html {
+    defy! {
+        div(class = "panel-block") {
+            span(class = classes!["icon", if props.breaking { "has-text-danger" } else { "has-text-info" }]) {
+                i(class = classes!["mdi", if props.breaking { "mdi-alert" } else { "mdi-information" }]);
+            }
+            div {
+                p(class = "is-family-monospace") { + &props.change.subject; }
+                p(class = "is-size-7") { + props.change.kind.to_string(); }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct ChangeBlockProps {
+    change: Change,
+    breaking: bool,
+}
@@ -0,0 +1,115 @@
+use defy::defy;
+use gloo::file::{Blob, ObjectUrl};
+use yew::prelude::*;
+use yew_hooks::use_clipboard;
+
+use crate::{
+    catalog,
+    data::{Data, MappingDef},
+    util::state_callback,
+    PluginFilter,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Markdown,
+    Json,
+}
+
+impl Format {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Markdown => "placeholders.md",
+            Self::Json => "placeholders.json",
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// Exports the mappings from the shown plugins as a catalog of available placeholders.
+#[function_component]
+pub fn CatalogExport(props: &Props) -> Html {
+    let format = use_state(|| Format::Markdown);
+
+    let shown = |mapping: &MappingDef| {
+        props
+            .plugins
+            .contains(mapping.metadata.source_plugin.as_ref())
+    };
+    let text = match *format {
+        Format::Markdown => catalog::markdown(&props.schema, shown),
+        Format::Json => catalog::json(&props.schema, shown),
+    };
+
+    let clipboard = use_clipboard();
+    let url = use_memo(
+        |(text, format)| {
+            ObjectUrl::from(Blob::new_with_options(
+                text.as_str(),
+                Some(format.mime_type()),
+            ))
+        },
+        (text.clone(), *format),
+    );
+
+    defy! {
+        div(class = "box") {
+            h2(class = "title is-5") {
+                + "Export catalog";
+            }
+            p(class = "block") {
+                + "A list of the placeholders from the selected plugins, e.g. for the README of a plugin.";
+            }
+
+            div(class = "tabs is-small") {
+                ul {
+                    for (tab, label) in [(Format::Markdown, "Markdown"), (Format::Json, "JSON")] {
+                        li(class = classes![(*format == tab).then_some("is-active")]) {
+                            a(onclick = state_callback(&format, tab)) { + label; }
+                        }
+                    }
+                }
+            }
+
+            div(class = "block") {
+                textarea(
+                    class = "textarea is-family-monospace is-size-7",
+                    rows = "16",
+                    readonly = true,
+                    value = text.clone(),
+                );
+            }
+
+            div(class = "buttons") {
+                button(class = "button", onclick = Callback::from({
+                    let clipboard = clipboard.clone();
+                    let text = text.clone();
+                    move |_| clipboard.write_text(text.clone())
+                })) {
+                    span(class = "icon") {
+                        i(class = "mdi mdi-content-copy");
+                    }
+                    span { + "Copy"; }
+                }
+                a(class = "button", href = url.to_string(), download = format.file_name()) {
+                    span(class = "icon") {
+                        i(class = "mdi mdi-download");
+                    }
+                    span { + "Download"; }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub schema: Data,
+    pub plugins: PluginFilter,
+}
@@ -1,85 +1,200 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use defy::defy;
 use yew::prelude::*;
-use yew_hooks::use_clipboard;
+use yew_router::prelude::*;
 
+use super::{
+    args::{ArgsForm, MappingRef},
+    graph::SchemaGraph,
+    mappings::mapping_route,
+    route_search::RouteSearch,
+};
 use crate::{
-    data::{Data, KindId, MappingDef},
-    util::set_state,
+    ast,
+    data::{Data, KindId, MappingDef, QualifiedRef, FQN_SEPARATOR},
+    document::{self, Chain, Step},
+    pathfind,
+    route::Route,
+    util::{set_state, state_callback},
     PluginFilter,
 };
 
+/// Edits a coalescence chain of alternative paths, separated by `|` in the template.
+///
+/// Each alternative is resolved from the root kind,
+/// and the first one that is non-null is used.
+#[function_component]
+pub fn CoalesceBuilder(props: &CoalesceBuilderProps) -> Html {
+    let update = |f: fn(&mut Chain, usize), i: usize| {
+        let chain = props.chain.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut chain = chain.clone();
+            f(&mut chain, i);
+            on_change.emit(chain);
+        })
+    };
+
+    defy! {
+        for (i, path) in props.chain.iter().enumerate() {
+            div(class = "level mb-1") {
+                div(class = "level-left") {
+                    h3(class = "heading level-item") {
+                        if i == 0 {
+                            + "Main expression";
+                        } else {
+                            + format!("Fallback #{i}");
+                        }
+                    }
+                }
+
+                if props.chain.len() > 1 {
+                    div(class = "level-right") {
+                        button(class = "button is-small is-danger is-light level-item", onclick = update(|chain, i| { chain.remove(i); }, i)) {
+                            span(class = "icon") {
+                                i(class = "mdi mdi-close");
+                            }
+                            span { + "Remove"; }
+                        }
+                    }
+                }
+            }
+
+            PathBuilder(
+                schema = props.schema.clone(),
+                root_kind = props.root_kind.clone(),
+                source_kind = props.root_kind.clone(),
+                target_kind = props.target_kind.clone(),
+                plugins = props.plugins.clone(),
+                path = path.clone(),
+                on_change = Callback::from({
+                    let chain = props.chain.clone();
+                    let on_change = props.on_change.clone();
+                    move |path| {
+                        let mut chain = chain.clone();
+                        chain[i] = path;
+                        on_change.emit(chain);
+                    }
+                }),
+            );
+        }
+
+        button(class = "button is-small mb-4", onclick = update(|chain, _| chain.push(Vec::new()), 0)) {
+            span(class = "icon") {
+                i(class = "mdi mdi-call-split");
+            }
+            span { + "Add fallback"; }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct CoalesceBuilderProps {
+    pub schema: Data,
+    /// The source kind of the whole template, from which all paths are resolved.
+    pub root_kind: KindId,
+    /// The kind that each path is expected to resolve to, if any.
+    #[prop_or_default]
+    pub target_kind: Option<KindId>,
+    pub plugins: PluginFilter,
+    pub chain: Chain,
+    pub on_change: Callback<Chain>,
+}
+
+/// Edits a path of mapping calls starting from `source_kind`.
 #[function_component]
-pub fn Expression(props: &Props) -> Html {
-    let path = use_state(Vec::<Step>::new);
-    let terminal_kind = match path.last() {
+pub fn PathBuilder(props: &PathBuilderProps) -> Html {
+    let terminal_kind = match props.path.last() {
         Some(step) => &step.mapping.target_kind,
         None => &props.source_kind,
     };
 
-    let selected_mapping = use_state(|| None::<Step>);
+    let selected_mapping = use_state(|| None::<Selection>);
 
     let push_mapping = Callback::from({
-        let path = path.clone();
+        let path = props.path.clone();
+        let on_change = props.on_change.clone();
+        let selected_mapping = selected_mapping.clone();
+
+        move |selection: Selection| {
+            let mut path_vec = path.clone();
+            path_vec.extend(selection.hops);
+            path_vec.push(selection.step);
+            on_change.emit(path_vec);
+            selected_mapping.set(None);
+        }
+    });
+
+    let push_route = Callback::from({
+        let path = props.path.clone();
+        let on_change = props.on_change.clone();
         let selected_mapping = selected_mapping.clone();
 
-        move |step| {
-            let mut path_vec = (*path).clone();
-            path_vec.push(step);
-            path.set(path_vec);
+        move |steps: Vec<Step>| {
+            let mut path_vec = path.clone();
+            path_vec.extend(steps);
+            on_change.emit(path_vec);
             selected_mapping.set(None);
         }
     });
 
     let truncate_steps = Callback::from({
-        let path = path.clone();
+        let path = props.path.clone();
+        let on_change = props.on_change.clone();
         let selected_mapping = selected_mapping.clone();
 
         move |i: Option<usize>| {
-            let mut path_vec = (*path).clone();
+            let mut path_vec = path.clone();
             path_vec.truncate(i.map_or(0, |i| i + 1));
-            path.set(path_vec);
+            on_change.emit(path_vec);
             selected_mapping.set(None);
         }
     });
 
-    let step_strings: Vec<_> = path
-        .iter()
-        .map(|step| step.minified_name.as_str())
-        .collect();
-    let template_string = format!("{{{}}}", step_strings.join(" "));
-
-    let clipboard = use_clipboard();
+    // mappings without parameters are appended directly, others are selected to fill in arguments
+    let choose_from_graph = Callback::from({
+        let schema = props.schema.clone();
+        let terminal_kind = terminal_kind.clone();
+        let push_mapping = push_mapping.clone();
+        let selected_mapping = selected_mapping.clone();
 
-    defy! {
-        div(class = "level") {
-            label(class = "label is-medium") {
-                + "Build your expression";
+        move |MappingRef(mapping)| {
+            let route = pathfind::implicit_routes(&schema, &terminal_kind)
+                .remove(&mapping.source_kind)
+                .unwrap_or_default();
+            let selection = Selection {
+                hops: route
+                    .into_iter()
+                    .map(|hop| Step::inserted(&schema, hop))
+                    .collect(),
+                step: Step::after_implicit(&schema, mapping, &terminal_kind),
+            };
+            match selection.step.args.is_empty() {
+                true => push_mapping.emit(selection),
+                false => selected_mapping.set(Some(selection)),
             }
+        }
+    });
+    let show_graph = use_state(|| false);
+    let written_after = document::written_after(&props.source_kind, &props.path);
 
-            div(class = "level") {
-                input(
-                    class = "input",
-                    type = "text", readonly = true,
-                    value = template_string.clone(),
-                );
-
-                button(class = "button", onclick = Callback::from({
-                    let clipboard = clipboard.clone();
-                    let template_string = template_string.clone();
-                    move |_| clipboard.write_text(template_string.clone())
-                })) {
-                    span(class = "icon") {
-                        i(class = "mdi mdi-content-copy");
-                    }
-                    span {
-                        + "Copy";
-                    }
-                }
+    let set_selected_args = Callback::from({
+        let selected_mapping = selected_mapping.clone();
+        move |args| {
+            if let Some(selection) = &*selected_mapping {
+                selected_mapping.set(Some(Selection {
+                    hops: selection.hops.clone(),
+                    step: Step {
+                        args,
+                        ..selection.step.clone()
+                    },
+                }));
             }
         }
+    });
 
+    defy! {
         div(class = "box") {
             nav(class = "breadcrumb", aria-label = "breadcrumbs") {
                 ul {
@@ -88,40 +203,128 @@ pub fn Expression(props: &Props) -> Html {
                         reset = truncate_steps.reform(|_| None),
                     );
 
-                    for (i, step) in path.iter().enumerate() {
+                    for (i, step) in props.path.iter().enumerate() {
                         StepButton(
-                            name = step.minified_name.clone(),
+                            name = step.call_string(),
+                            inserted = step.inserted,
+                            ambiguous = step.is_ambiguous(&props.schema, written_after[i]),
                             reset = truncate_steps.reform(move |_| Some(i)),
                         );
                     }
                 }
             }
+
+            for (i, step) in props.path.iter().enumerate() {
+                if step.is_ambiguous(&props.schema, written_after[i]) {
+                    div(class = "level mb-1") {
+                        div(class = "level-left") {
+                            p(class = "help is-warning level-item") {
+                                + format!("`{}` may refer to another mapping when called after {}", step.minified_name, written_after[i].0);
+                            }
+                        }
+                        div(class = "level-right") {
+                            button(class = "button is-small is-warning is-light level-item", onclick = props.on_change.reform({
+                                let path = props.path.clone();
+                                move |_| {
+                                    let mut path = path.clone();
+                                    path[i].minified_name = path[i].mapping.name.0.clone();
+                                    path
+                                }
+                            })) {
+                                + format!("Use {}", step.mapping.name.0);
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(expected) = &props.target_kind {
+                if expected != terminal_kind {
+                    p(class = "help is-warning") {
+                        + format!("The expression should resolve to {}, but currently resolves to {}", expected.0, terminal_kind.0);
+                    }
+                }
+            }
         }
 
         div(class = "box") {
-            h2(class = "heading") { + "Transform"; }
+            div(class = "level mb-2") {
+                div(class = "level-left") {
+                    h2(class = "heading level-item") { + "Transform"; }
+                }
+                div(class = "level-right") {
+                    div(class = "tabs is-small is-toggle level-item") {
+                        ul {
+                            li(class = classes![(!*show_graph).then_some("is-active")]) {
+                                a(onclick = state_callback(&show_graph, false)) {
+                                    span(class = "icon is-small") { i(class = "mdi mdi-format-list-bulleted"); }
+                                    span { + "List"; }
+                                }
+                            }
+                            li(class = classes![show_graph.then_some("is-active")]) {
+                                a(onclick = state_callback(&show_graph, true)) {
+                                    span(class = "icon is-small") { i(class = "mdi mdi-graph-outline"); }
+                                    span { + "Graph"; }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
 
             div(class = "columns") {
-                div(class = "column") {
-                    MappingList(
-                        kind = terminal_kind.clone(),
-                        plugins = props.plugins.clone(),
-                        schema = props.schema.clone(),
-                        choose_mapping = set_state(&selected_mapping).reform(Some),
-                    );
+                div(class = classes!["column", show_graph.then_some("is-two-thirds")]) {
+                    if *show_graph {
+                        SchemaGraph(
+                            schema = props.schema.clone(),
+                            plugins = props.plugins.clone(),
+                            focus = Some(terminal_kind.clone()),
+                            on_choose_mapping = Some(choose_from_graph),
+                        );
+                    } else {
+                        MappingList(
+                            kind = terminal_kind.clone(),
+                            plugins = props.plugins.clone(),
+                            schema = props.schema.clone(),
+                            choose_mapping = set_state(&selected_mapping).reform(Some),
+                        );
+                    }
                 }
 
                 div(class = "column") {
-                    if let Some(mapping) = &*selected_mapping {
+                    if let Some(selection) = &*selected_mapping {
+                        let mapping = &selection.step;
                         article {
                             div(class = "message-header") {
                                 + mapping.mapping.name.0.clone();
+                                Link<Route>(to = mapping_route(&mapping.mapping.source_kind, &mapping.mapping.name)) {
+                                    span(class = "icon has-text-white", title = "Details") {
+                                        i(class = "mdi mdi-information-outline");
+                                    }
+                                }
                             }
                             div(class = "message-body") {
                                 p { + &mapping.mapping.help; }
+
+                                if !selection.hops.is_empty() {
+                                    p(class = "is-size-7") {
+                                        + "The server inserts these implicit conversions before this mapping: ";
+                                        + selection.hops.iter().map(|hop| hop.mapping.name.0.as_str()).collect::<Vec<_>>().join(", ");
+                                    }
+                                }
+
+                                ArgsForm(
+                                    schema = props.schema.clone(),
+                                    root_kind = props.root_kind.clone(),
+                                    plugins = props.plugins.clone(),
+                                    mapping = MappingRef(mapping.mapping.clone()),
+                                    args = mapping.args.clone(),
+                                    on_change = set_selected_args.clone(),
+                                );
+
                                 button(class = "button is-primary", onclick = push_mapping.reform({
-                                    let mapping = mapping.clone();
-                                    move |_| mapping.clone()
+                                    let selection = selection.clone();
+                                    move |_| selection.clone()
                                 })) {
                                     span(class = "icon") {
                                         i(class = "mdi mdi-plus");
@@ -133,21 +336,52 @@ pub fn Expression(props: &Props) -> Html {
                 }
             }
         }
+
+        div(class = "box") {
+            h2(class = "heading") { + "Find a path"; }
+
+            RouteSearch(
+                schema = props.schema.clone(),
+                plugins = props.plugins.clone(),
+                source_kind = terminal_kind.clone(),
+                target_kind = props.target_kind.clone(),
+                on_insert = push_route,
+            );
+        }
     }
 }
 
 #[derive(PartialEq, Properties)]
-pub struct Props {
-    pub source_kind: KindId,
+pub struct PathBuilderProps {
     pub schema: Data,
+    /// The source kind of the whole template, from which argument expressions are resolved.
+    pub root_kind: KindId,
+    pub source_kind: KindId,
+    /// The kind that the path is expected to resolve to, if any.
+    #[prop_or_default]
+    pub target_kind: Option<KindId>,
     pub plugins: PluginFilter,
+    pub path: Vec<Step>,
+    pub on_change: Callback<Vec<Step>>,
 }
 
 #[function_component]
 fn StepButton(props: &StepProps) -> Html {
     defy! {
         li {
-            button(class = classes!["button", "is-link"], onclick = props.reset.reform(|_| ())) {
+            button(
+                class = classes![
+                    "button",
+                    if props.ambiguous { "is-warning" } else { "is-link" },
+                    props.inserted.then_some("is-light"),
+                ],
+                title = match (props.inserted, props.ambiguous) {
+                    (true, _) => Some("Implicit conversion inserted by the server"),
+                    (false, true) => Some("This name may also refer to another mapping"),
+                    (false, false) => None,
+                },
+                onclick = props.reset.reform(|_| ()),
+            ) {
                 if let Some(icon) = &props.icon {
                     span(class = "icon") {
                         i(class = classes!["mdi", icon.clone()]);
@@ -170,48 +404,211 @@ struct StepProps {
     name: String,
     #[prop_or_default]
     icon: Option<Classes>,
+    #[prop_or_default]
+    inserted: bool,
+    #[prop_or_default]
+    ambiguous: bool,
     reset: Callback<()>,
 }
 
+/// Lists the mappings applicable to `kind`,
+/// including those reachable through implicit conversions.
 #[function_component]
 fn MappingList(props: &MappingListProps) -> Html {
-    let Some(mappings) = props.schema.mappings.get(&props.kind) else {
-        return defy! {
+    let schema = &props.schema;
+
+    let search = use_state(String::new);
+    let query = search.trim();
+    let query_error = !query.is_empty()
+        && query
+            .split(FQN_SEPARATOR)
+            .any(|token| token.is_empty() || !token.chars().all(ast::is_fqn_token_char));
+    // an invalid query cannot be parsed by the server, so nothing matches it
+    let query = (!query.is_empty()).then(|| QualifiedRef::parse(query));
+
+    let mut routes: Vec<_> = pathfind::implicit_routes(schema, &props.kind)
+        .into_iter()
+        .collect();
+    routes.sort_by_key(|(_, route)| route.len());
+
+    let groups: Vec<_> = routes
+        .iter()
+        .filter_map(|(kind, route)| {
+            let shown = |mapping: &MappingDef| {
+                props
+                    .plugins
+                    .contains(mapping.metadata.source_plugin.as_ref())
+            };
+            let missing = |mapping: &MappingDef| match &query {
+                _ if query_error => None,
+                Some(query) => mapping.name.matches(query),
+                None => Some(0),
+            };
+
+            // aliases are listed under their canonical mapping instead of separately
+            let mut mappings: Vec<_> = schema
+                .mappings
+                .get(kind)?
+                .values()
+                .filter(|mapping| mapping.metadata.alias_of.is_none() && shown(mapping))
+                .filter_map(|mapping| {
+                    let aliases: Vec<_> = schema
+                        .aliases_of(mapping)
+                        .filter(|alias| shown(alias))
+                        .collect();
+                    let missing = std::iter::once(mapping)
+                        .chain(aliases.iter().copied())
+                        .filter_map(|mapping| missing(mapping))
+                        .min()?;
+                    Some((mapping, aliases, missing))
+                })
+                .collect();
+            // fewer missing tokens is a better match, same as the path finder score
+            mappings.sort_by_key(|&(_, _, missing)| missing);
+
+            let to_item = |mapping: &Arc<MappingDef>| {
+                Step::after_implicit(schema, mapping.clone(), &props.kind)
+            };
+            let mappings: Vec<_> = mappings
+                .into_iter()
+                .map(|(mapping, aliases, _)| {
+                    (
+                        to_item(mapping),
+                        aliases.into_iter().map(to_item).collect::<Vec<_>>(),
+                    )
+                })
+                .collect();
+            let route: Vec<_> = route.iter().cloned().map(MappingRef).collect();
+            (!mappings.is_empty()).then_some((kind, route, mappings))
+        })
+        .collect();
+
+    defy! {
+        div(class = "field") {
+            div(class = "control has-icons-left") {
+                input(
+                    class = classes!["input", "is-small", query_error.then_some("is-danger")],
+                    type = "search",
+                    placeholder = "Search by name, e.g. foo:money",
+                    value = (*search).clone(),
+                    oninput = Callback::from({
+                        let search = search.clone();
+                        move |event: InputEvent| {
+                            let input: web_sys::HtmlInputElement = event.target_unchecked_into();
+                            search.set(input.value());
+                        }
+                    }),
+                );
+                span(class = "icon is-small is-left") {
+                    i(class = "mdi mdi-magnify");
+                }
+            }
+            if query_error {
+                p(class = "help is-danger") {
+                    + "Names can only contain letters, digits, `_` and `-`, separated by `:`";
+                }
+            }
+        }
+
+        if groups.is_empty() {
             span {
                 + "No mappings";
             }
-        };
+        }
+
+        for (kind, route, mappings) in groups {
+            if !route.is_empty() {
+                p(class = "heading mt-3") {
+                    + format!("Via implicit conversion to {}", kind.0);
+                }
+            }
+
+            for (step, aliases) in mappings {
+                div(class = "buttons has-addons mb-0") {
+                    MappingButton(
+                        schema = schema.clone(),
+                        route = route.clone(),
+                        step = step,
+                        full_name = query.is_some(),
+                        choose_mapping = props.choose_mapping.clone(),
+                    );
+                    for alias in aliases {
+                        MappingButton(
+                            schema = schema.clone(),
+                            route = route.clone(),
+                            step = alias,
+                            full_name = query.is_some(),
+                            alias = true,
+                            choose_mapping = props.choose_mapping.clone(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[function_component]
+fn MappingButton(props: &MappingButtonProps) -> Html {
+    let title = match props.alias {
+        true => props
+            .step
+            .mapping
+            .metadata
+            .alias_of
+            .as_ref()
+            .map(|alias_of| format!("Alias of {alias_of}")),
+        false => None,
     };
 
     defy! {
-        for mapping in mappings.values() {
-            if mapping.metadata.alias_of.is_none() && props.plugins.contains(mapping.metadata.source_plugin.as_ref()) {
-                let minified_name = mapping.name.minify(mappings.keys());
-
-                button(class = "button", onclick = props.choose_mapping.reform({
-                    let mapping = mapping.clone();
-                    move |_| Step {
-                        mapping: mapping.clone(),
-                        minified_name: minified_name.clone(),
-                    }
-                })) {
-                    + mapping.name.last();
+        button(
+            class = classes!["button", props.alias.then_some("is-light")],
+            title = title,
+            onclick = props.choose_mapping.reform({
+                let schema = props.schema.clone();
+                let route = props.route.clone();
+                let step = props.step.clone();
+                move |_| Selection {
+                    hops: route.iter().map(|MappingRef(mapping)| Step::inserted(&schema, mapping.clone())).collect(),
+                    step: step.clone(),
                 }
+            }),
+        ) {
+            if props.full_name {
+                + &props.step.mapping.name.0;
+            } else {
+                + props.step.mapping.name.last();
             }
         }
     }
 }
 
+#[derive(PartialEq, Properties)]
+struct MappingButtonProps {
+    schema: Data,
+    /// The implicit mappings inserted before the step.
+    route: Vec<MappingRef>,
+    step: Step,
+    /// Whether to show the fully qualified name instead of the last component.
+    full_name: bool,
+    /// Whether the mapping is an alias of another mapping, shown in a lighter style.
+    #[prop_or_default]
+    alias: bool,
+    choose_mapping: Callback<Selection>,
+}
+
 #[derive(PartialEq, Properties)]
 struct MappingListProps {
     kind: KindId,
     plugins: PluginFilter,
     schema: Data,
-    choose_mapping: Callback<Step>,
+    choose_mapping: Callback<Selection>,
 }
 
+/// A mapping chosen from the list, together with the implicit conversions needed to reach it.
 #[derive(Clone)]
-struct Step {
-    mapping: Rc<MappingDef>,
-    minified_name: String,
+struct Selection {
+    hops: Vec<Step>,
+    step: Step,
 }
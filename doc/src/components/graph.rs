@@ -0,0 +1,355 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use defy::defy;
+use yew::prelude::*;
+
+use super::{args::MappingRef, kinds::KindLink, mappings::MappingLink};
+use crate::{
+    data::{Data, KindId, MappingDef},
+    graph::{self, NODE_HEIGHT},
+    pathfind, PluginFilter,
+};
+
+const ZOOM_STEP: f64 = 1.25;
+
+/// Draws the schema as a graph with kinds as nodes and mappings as edges.
+///
+/// Drag the graph to pan it. Clicking a kind or an edge lists the mappings involved.
+#[function_component]
+pub fn SchemaGraph(props: &Props) -> Html {
+    let schema = &props.schema;
+    let layout = use_memo(
+        |(schema, plugins)| graph::layout(schema, plugins),
+        (schema.clone(), props.plugins.clone()),
+    );
+
+    let view = use_state(View::default);
+    let drag = use_mut_ref(Drag::default);
+    let dragging = use_state(|| false);
+    let svg = use_node_ref();
+    let selected = use_state(|| None::<Selected>);
+
+    // kinds that the focused kind is implicitly converted to, sorted by route length,
+    // and the mappings that can be appended from there
+    let reachable = use_memo(
+        |(schema, focus)| {
+            let mut routes: Vec<_> = focus
+                .iter()
+                .flat_map(|kind| pathfind::implicit_routes(schema, kind))
+                .collect();
+            routes.sort_by_key(|(_, route)| route.len());
+
+            let available: BTreeSet<_> = routes
+                .iter()
+                .filter_map(|(kind, _)| schema.mappings.get(kind))
+                .flat_map(|mappings| mappings.values())
+                .map(|mapping| (mapping.source_kind.clone(), mapping.name.clone()))
+                .collect();
+            (routes, available)
+        },
+        (schema.clone(), props.focus.clone()),
+    );
+    let (routes, available) = &*reachable;
+    let is_available = |mapping: &Arc<MappingDef>| {
+        props.on_choose_mapping.is_some()
+            && available.contains(&(mapping.source_kind.clone(), mapping.name.clone()))
+    };
+
+    let view_width = layout.width / view.zoom;
+    let view_height = layout.height / view.zoom;
+    let zoom = |factor: f64| {
+        let view = view.clone();
+        let (width, height) = (layout.width, layout.height);
+        Callback::from(move |_: MouseEvent| {
+            // zoom around the center of the visible area
+            let zoom = view.zoom * factor;
+            view.set(View {
+                x: view.x + (width / view.zoom - width / zoom) / 2.0,
+                y: view.y + (height / view.zoom - height / zoom) / 2.0,
+                zoom,
+            });
+        })
+    };
+    let focus_node = props.focus.as_ref().and_then(|kind| layout.node(kind));
+    let center_focus = focus_node.map(|node| {
+        let view = view.clone();
+        let (x, y) = (node.x - view_width / 2.0, node.y - view_height / 2.0);
+        Callback::from(move |_: MouseEvent| {
+            view.set(View {
+                x,
+                y,
+                zoom: view.zoom,
+            })
+        })
+    });
+
+    let on_mouse_down = Callback::from({
+        let drag = drag.clone();
+        let view = view.clone();
+        move |event: MouseEvent| {
+            *drag.borrow_mut() = Drag {
+                start: Some((event.client_x(), event.client_y(), *view)),
+                moved: false,
+            };
+        }
+    });
+    let on_mouse_move = Callback::from({
+        let drag = drag.clone();
+        let view = view.clone();
+        let dragging = dragging.clone();
+        let svg = svg.clone();
+        move |event: MouseEvent| {
+            let mut drag = drag.borrow_mut();
+            let Some((client_x, client_y, start)) = drag.start else {
+                return;
+            };
+            let Some(element) = svg.cast::<web_sys::Element>() else {
+                return;
+            };
+            // the view box is scaled uniformly to fit the element
+            let scale = f64::max(
+                view_width / f64::from(element.client_width().max(1)),
+                view_height / f64::from(element.client_height().max(1)),
+            );
+            drag.moved = true;
+            dragging.set(true);
+            view.set(View {
+                x: start.x - f64::from(event.client_x() - client_x) * scale,
+                y: start.y - f64::from(event.client_y() - client_y) * scale,
+                zoom: start.zoom,
+            });
+        }
+    });
+    let on_mouse_up = Callback::from({
+        let drag = drag.clone();
+        let dragging = dragging.clone();
+        move |_: MouseEvent| {
+            drag.borrow_mut().start = None;
+            dragging.set(false);
+        }
+    });
+    // a click that ends a drag does not select anything
+    let select = |selection: Selected| {
+        let selected = selected.clone();
+        let drag = drag.clone();
+        Callback::from(move |_: MouseEvent| {
+            if !drag.borrow().moved {
+                selected.set(Some(selection.clone()));
+            }
+        })
+    };
+
+    let selected_mappings: Vec<_> = match &*selected {
+        Some(Selected::Kind(kind)) => layout
+            .edges
+            .iter()
+            .filter(|edge| &edge.from == kind)
+            .flat_map(|edge| edge.mappings.iter().cloned())
+            .collect(),
+        Some(Selected::Edge(from, to, is_implicit)) => layout
+            .edges
+            .iter()
+            .filter(|edge| &edge.from == from && &edge.to == to && edge.is_implicit == *is_implicit)
+            .flat_map(|edge| edge.mappings.iter().cloned())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    defy! {
+        div(class = "buttons are-small") {
+            button(class = "button", title = "Zoom in", onclick = zoom(ZOOM_STEP)) {
+                span(class = "icon") { i(class = "mdi mdi-magnify-plus-outline"); }
+            }
+            button(class = "button", title = "Zoom out", onclick = zoom(1.0 / ZOOM_STEP)) {
+                span(class = "icon") { i(class = "mdi mdi-magnify-minus-outline"); }
+            }
+            button(class = "button", title = "Show the whole graph", onclick = {
+                let view = view.clone();
+                Callback::from(move |_| view.set(View::default()))
+            }) {
+                span(class = "icon") { i(class = "mdi mdi-fit-to-page-outline"); }
+            }
+            if let Some(center_focus) = center_focus {
+                button(class = "button", onclick = center_focus) {
+                    span(class = "icon") { i(class = "mdi mdi-crosshairs-gps"); }
+                    span { + "Current kind"; }
+                }
+            }
+        }
+
+        svg(
+            class = classes!["schema-graph", "box", "p-0", dragging.then_some("is-dragging")],
+            viewBox = format!("{} {} {view_width} {view_height}", view.x, view.y),
+            ref = svg.clone(),
+            onmousedown = on_mouse_down,
+            onmousemove = on_mouse_move,
+            onmouseup = on_mouse_up.clone(),
+            onmouseleave = on_mouse_up,
+        ) {
+            defs {
+                marker(id = "graph-arrow", class = "graph-arrow", viewBox = "0 0 10 10", refX = "10", refY = "5", markerWidth = "6", markerHeight = "6", orient = "auto") {
+                    path(d = "M 0 0 L 10 5 L 0 10 z");
+                }
+            }
+
+            for edge in &layout.edges {
+                let is_selected = *selected == Some(Selected::Edge(edge.from.clone(), edge.to.clone(), edge.is_implicit));
+                g(
+                    class = classes![
+                        "graph-edge",
+                        edge.is_implicit.then_some("is-implicit"),
+                        edge.mappings.iter().any(is_available).then_some("is-available"),
+                        is_selected.then_some("is-selected"),
+                    ],
+                    onclick = select(Selected::Edge(edge.from.clone(), edge.to.clone(), edge.is_implicit)),
+                ) {
+                    title {
+                        + edge_title(edge);
+                    }
+                    path(class = "graph-edge-hit", d = edge.path.clone());
+                    path(class = "graph-edge-line", d = edge.path.clone());
+                }
+            }
+
+            for node in &layout.nodes {
+                let def = schema.kinds.get(&node.kind);
+                g(
+                    class = classes![
+                        "graph-node",
+                        def.is_some_and(|def| def.metadata.is_root).then_some("is-root"),
+                        def.is_some_and(|def| def.can_display).then_some("is-displayable"),
+                        routes.iter().any(|(kind, _)| kind == &node.kind).then_some("is-reachable"),
+                        (props.focus.as_ref() == Some(&node.kind)).then_some("is-focus"),
+                        (*selected == Some(Selected::Kind(node.kind.clone()))).then_some("is-selected"),
+                    ],
+                    onclick = select(Selected::Kind(node.kind.clone())),
+                ) {
+                    title {
+                        + def.map_or("This kind is not defined in the loaded schemas", |def| def.help.as_str());
+                    }
+                    rect(
+                        x = (node.x - node.width / 2.0).to_string(),
+                        y = (node.y - NODE_HEIGHT / 2.0).to_string(),
+                        width = node.width.to_string(),
+                        height = NODE_HEIGHT.to_string(),
+                        rx = "4",
+                    );
+                    text(x = node.x.to_string(), y = node.y.to_string()) {
+                        + &node.kind.0;
+                    }
+                }
+            }
+        }
+
+        p(class = "help block") {
+            + "Dashed edges are implicit mappings inserted by the server automatically.";
+            if props.on_choose_mapping.is_some() {
+                + " Highlighted edges can be appended to the current path.";
+            }
+        }
+
+        match &*selected {
+            Some(Selected::Kind(kind)) => {
+                h3(class = "title is-6") {
+                    + "Mappings from ";
+                    KindLink(kind = kind.clone());
+                }
+            }
+            Some(Selected::Edge(from, to, _)) => {
+                h3(class = "title is-6") {
+                    + "Mappings from ";
+                    KindLink(kind = from.clone());
+                    + " to ";
+                    KindLink(kind = to.clone());
+                }
+            }
+            None => {}
+        }
+
+        if selected.is_some() && selected_mappings.is_empty() {
+            p { + "No mappings"; }
+        }
+
+        for mapping in selected_mappings {
+            div(class = "level mb-2") {
+                div(class = "level-left") {
+                    div(class = "level-item") {
+                        MappingLink(kind = mapping.source_kind.clone(), name = mapping.name.clone());
+                        + " → ";
+                        KindLink(kind = mapping.target_kind.clone());
+                        if mapping.is_implicit {
+                            span(class = "tag is-light ml-1") { + "implicit"; }
+                        }
+                    }
+                }
+                if let Some(on_choose_mapping) = props.on_choose_mapping.as_ref().filter(|_| is_available(&mapping)) {
+                    div(class = "level-right") {
+                        button(class = "button is-small is-primary level-item", onclick = on_choose_mapping.reform({
+                            let mapping = mapping.clone();
+                            move |_| MappingRef(mapping.clone())
+                        })) {
+                            span(class = "icon") { i(class = "mdi mdi-plus"); }
+                            span { + "Append"; }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub schema: Data,
+    pub plugins: PluginFilter,
+    /// The kind at the end of the path being edited, which is highlighted.
+    #[prop_or_default]
+    pub focus: Option<KindId>,
+    /// Called when a mapping usable from `focus` is chosen.
+    ///
+    /// Mappings cannot be chosen if this is not set.
+    #[prop_or_default]
+    pub on_choose_mapping: Option<Callback<MappingRef>>,
+}
+
+/// The visible area of the graph, in layout coordinates.
+#[derive(Clone, Copy)]
+struct View {
+    x: f64,
+    y: f64,
+    zoom: f64,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Drag {
+    /// The client position of the mouse and the view when the drag started.
+    start: Option<(i32, i32, View)>,
+    /// Whether the mouse has moved since the drag started.
+    moved: bool,
+}
+
+#[derive(Clone, PartialEq)]
+enum Selected {
+    Kind(KindId),
+    /// The source and target kinds, and whether the edge is implicit.
+    Edge(KindId, KindId, bool),
+}
+
+fn edge_title(edge: &graph::Edge) -> String {
+    let names: Vec<_> = edge
+        .mappings
+        .iter()
+        .map(|mapping| mapping.name.0.as_str())
+        .collect();
+    names.join("\n")
+}
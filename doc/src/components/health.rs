@@ -0,0 +1,67 @@
+use defy::defy;
+use yew::prelude::*;
+
+use crate::{
+    data::Data,
+    diagnostics::Severity,
+    health::{self, Category},
+};
+
+/// Lists inconsistencies in the loaded schema, grouped by category.
+#[function_component]
+pub fn SchemaHealth(props: &Props) -> Html {
+    let issues = use_memo(|schema| health::check(schema), props.schema.clone());
+
+    let mut groups: Vec<(Category, Vec<&health::Issue>)> = Vec::new();
+    for issue in issues.iter() {
+        match groups.last_mut() {
+            Some((category, group)) if *category == issue.category => group.push(issue),
+            _ => groups.push((issue.category, vec![issue])),
+        }
+    }
+
+    defy! {
+        div(class = "box panel") {
+            p(class = "panel-heading") {
+                + "Schema health";
+            }
+
+            if groups.is_empty() {
+                div(class = "panel-block") {
+                    span(class = "icon has-text-success") {
+                        i(class = "mdi mdi-check-circle");
+                    }
+                    + "No problems found in the loaded schemas.";
+                }
+            }
+
+            for (category, group) in groups {
+                div(class = "panel-block") {
+                    strong {
+                        + format!("{} ({})", category.title(), group.len());
+                    }
+                }
+
+                for issue in group {
+                    div(class = "panel-block") {
+                        span(class = classes!["icon", match issue.severity {
+                            Severity::Error => "has-text-danger",
+                            Severity::Warning => "has-text-warning",
+                        }]) {
+                            i(class = "mdi mdi-alert");
+                        }
+                        div {
+                            p(class = "is-family-monospace") { + &issue.subject; }
+                            p(class = "is-size-7") { + &issue.message; }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub schema: Data,
+}
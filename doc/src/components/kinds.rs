@@ -0,0 +1,220 @@
+use defy::defy;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use super::{args::MappingRef, mappings::MappingLink};
+use crate::{
+    data::{Data, KindId},
+    route::Route,
+};
+
+/// Lists all kinds in the schema.
+#[function_component]
+pub fn KindList(props: &KindListProps) -> Html {
+    defy! {
+        h1(class = "title") {
+            + "Kinds";
+        }
+
+        table(class = "table is-fullwidth is-hoverable") {
+            thead {
+                tr {
+                    th { + "Kind"; }
+                    th { + "Description"; }
+                    th { + "Mappings"; }
+                }
+            }
+            tbody {
+                for (kind, def) in &props.schema.kinds {
+                    tr {
+                        td {
+                            KindLink(kind = kind.clone());
+                            KindTags(schema = props.schema.clone(), kind = kind.clone());
+                        }
+                        td { + &def.help; }
+                        td {
+                            + props.schema.mappings.get(kind).map_or(0, |mappings| mappings.len()).to_string();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct KindListProps {
+    pub schema: Data,
+}
+
+/// Shows a kind together with the mappings and parameters that refer to it.
+#[function_component]
+pub fn KindPage(props: &KindPageProps) -> Html {
+    let schema = &props.schema;
+    let Some(def) = schema.kinds.get(&props.kind) else {
+        return defy! {
+            article(class = "message is-danger") {
+                div(class = "message-body") {
+                    + format!("There is no kind called {}.", props.kind.0);
+                }
+            }
+        };
+    };
+
+    let all_mappings = || {
+        schema
+            .mappings
+            .values()
+            .flat_map(|mappings| mappings.values())
+    };
+    let outgoing: Vec<_> = schema
+        .mappings
+        .get(&props.kind)
+        .into_iter()
+        .flat_map(|mappings| mappings.values())
+        .collect();
+    let incoming: Vec<_> = all_mappings()
+        .filter(|mapping| mapping.target_kind == props.kind)
+        .collect();
+    let accepting: Vec<_> = all_mappings()
+        .flat_map(|mapping| {
+            mapping
+                .parameters
+                .iter()
+                .filter(|param| param.kind == props.kind)
+                .map(move |param| (mapping, param))
+        })
+        .collect();
+
+    defy! {
+        nav(class = "breadcrumb") {
+            ul {
+                li {
+                    Link<Route>(to = Route::Kinds) { + "Kinds"; }
+                }
+                li(class = "is-active") {
+                    a { + &props.kind.0; }
+                }
+            }
+        }
+
+        h1(class = "title is-family-monospace") {
+            + &props.kind.0;
+        }
+        KindTags(schema = schema.clone(), kind = props.kind.clone());
+
+        div(class = "content mt-3") {
+            p { + &def.help; }
+            p(class = "is-size-7") {
+                if let Some(plugin) = &def.metadata.source_plugin {
+                    + format!("Provided by {plugin}. ");
+                }
+                + format!("Loaded from {}.", def.origin);
+            }
+        }
+
+        h2(class = "title is-5") { + format!("Mappings from this kind ({})", outgoing.len()); }
+        MappingTable(mappings = outgoing.into_iter().cloned().map(MappingRef).collect::<Vec<_>>());
+
+        h2(class = "title is-5") { + format!("Mappings to this kind ({})", incoming.len()); }
+        MappingTable(mappings = incoming.into_iter().cloned().map(MappingRef).collect::<Vec<_>>());
+
+        h2(class = "title is-5") { + format!("Parameters accepting this kind ({})", accepting.len()); }
+        table(class = "table is-fullwidth") {
+            tbody {
+                for (mapping, param) in accepting {
+                    tr {
+                        td(class = "is-family-monospace") { + &param.name.0; }
+                        td {
+                            + "of ";
+                            MappingLink(kind = mapping.source_kind.clone(), name = mapping.name.clone());
+                            + " on ";
+                            KindLink(kind = mapping.source_kind.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct KindPageProps {
+    pub schema: Data,
+    pub kind: KindId,
+}
+
+#[function_component]
+fn MappingTable(props: &MappingTableProps) -> Html {
+    defy! {
+        table(class = "table is-fullwidth") {
+            thead {
+                tr {
+                    th { + "Mapping"; }
+                    th { + "From"; }
+                    th { + "To"; }
+                    th { + "Description"; }
+                }
+            }
+            tbody {
+                for MappingRef(mapping) in &props.mappings {
+                    tr {
+                        td {
+                            MappingLink(kind = mapping.source_kind.clone(), name = mapping.name.clone());
+                            if mapping.is_implicit {
+                                span(class = "tag is-light ml-1") { + "implicit"; }
+                            }
+                            if mapping.metadata.alias_of.is_some() {
+                                span(class = "tag is-light ml-1") { + "alias"; }
+                            }
+                        }
+                        td { KindLink(kind = mapping.source_kind.clone()); }
+                        td { KindLink(kind = mapping.target_kind.clone()); }
+                        td { + &mapping.help; }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct MappingTableProps {
+    mappings: Vec<MappingRef>,
+}
+
+/// A link to the page of a kind.
+#[function_component]
+pub fn KindLink(props: &KindLinkProps) -> Html {
+    defy! {
+        Link<Route>(to = Route::Kind { id: props.kind.0.clone() }, classes = classes!["is-family-monospace"]) {
+            + &props.kind.0;
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct KindLinkProps {
+    pub kind: KindId,
+}
+
+#[function_component]
+fn KindTags(props: &KindTagsProps) -> Html {
+    let def = props.schema.kinds.get(&props.kind);
+    defy! {
+        span(class = "tags is-inline-flex ml-2 mb-0") {
+            if def.is_some_and(|def| def.can_display) {
+                span(class = "tag is-success is-light") { + "displayable"; }
+            }
+            if def.is_some_and(|def| def.metadata.is_root) {
+                span(class = "tag is-info is-light") { + "template"; }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct KindTagsProps {
+    schema: Data,
+    kind: KindId,
+}
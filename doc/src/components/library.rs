@@ -0,0 +1,178 @@
+use defy::defy;
+use yew::prelude::*;
+
+use crate::{
+    route::EditorQuery,
+    storage::{self, Draft},
+};
+
+/// Lists the templates saved in local storage.
+#[function_component]
+pub fn TemplateLibrary(props: &Props) -> Html {
+    let drafts = use_state(storage::load_library);
+    let new_name = use_state(String::new);
+    // the index and the edited name of the draft being renamed
+    let renaming = use_state(|| None::<(usize, String)>);
+
+    let save_current = edit_library(&drafts, {
+        let current = props.current.clone();
+        let new_name = new_name.clone();
+        move |drafts| {
+            let name = match new_name.trim() {
+                "" => format!("Untitled #{}", drafts.len() + 1),
+                name => name.to_string(),
+            };
+            drafts.push(Draft {
+                name,
+                state: current.clone(),
+            });
+            new_name.set(String::new());
+        }
+    });
+
+    defy! {
+        div(class = "box panel") {
+            p(class = "panel-heading") {
+                + "My templates";
+            }
+
+            div(class = "panel-block") {
+                div(class = "field has-addons is-flex-grow-1") {
+                    div(class = "control is-expanded") {
+                        input(
+                            class = "input is-small",
+                            type = "text",
+                            placeholder = "Name of the current template",
+                            value = (*new_name).clone(),
+                            oninput = text_input(&new_name),
+                        );
+                    }
+                    div(class = "control") {
+                        button(class = "button is-small is-primary", onclick = save_current) {
+                            span(class = "icon") { i(class = "mdi mdi-content-save"); }
+                            span { + "Save"; }
+                        }
+                    }
+                }
+            }
+
+            if drafts.is_empty() {
+                div(class = "panel-block") {
+                    p(class = "is-size-7") {
+                        + "No saved templates yet.";
+                    }
+                }
+            }
+
+            for (index, draft) in drafts.iter().enumerate() {
+                div(class = "panel-block") {
+                    div(class = "level is-flex-grow-1") {
+                        div(class = "level-left") {
+                            match &*renaming {
+                                Some((renaming_index, name)) if *renaming_index == index => {
+                                    div(class = "field has-addons level-item") {
+                                        div(class = "control") {
+                                            input(
+                                                class = "input is-small",
+                                                type = "text",
+                                                value = name.clone(),
+                                                oninput = Callback::from({
+                                                    let renaming = renaming.clone();
+                                                    move |event: InputEvent| {
+                                                        let input: web_sys::HtmlInputElement = event.target_unchecked_into();
+                                                        renaming.set(Some((index, input.value())));
+                                                    }
+                                                }),
+                                            );
+                                        }
+                                        div(class = "control") {
+                                            button(class = "button is-small is-primary", onclick = edit_library(&drafts, {
+                                                let renaming = renaming.clone();
+                                                let name = name.clone();
+                                                move |drafts| {
+                                                    drafts[index].name = name.clone();
+                                                    renaming.set(None);
+                                                }
+                                            })) {
+                                                span(class = "icon") { i(class = "mdi mdi-check"); }
+                                            }
+                                        }
+                                    }
+                                }
+                                _ => {
+                                    a(class = "level-item", onclick = props.on_open.reform({
+                                        let state = draft.state.clone();
+                                        move |_| state.clone()
+                                    })) {
+                                        + &draft.name;
+                                    }
+                                    span(class = "level-item is-size-7 is-family-monospace") {
+                                        + draft.state.template.as_deref().unwrap_or_default();
+                                    }
+                                }
+                            }
+                        }
+
+                        div(class = "level-right") {
+                            div(class = "buttons has-addons level-item") {
+                                button(class = "button is-small", title = "Rename", onclick = Callback::from({
+                                    let renaming = renaming.clone();
+                                    let name = draft.name.clone();
+                                    move |_| renaming.set(Some((index, name.clone())))
+                                })) {
+                                    span(class = "icon") { i(class = "mdi mdi-rename-box"); }
+                                }
+                                button(class = "button is-small", title = "Duplicate", onclick = edit_library(&drafts, move |drafts| {
+                                    let mut copy = drafts[index].clone();
+                                    copy.name = format!("{} (copy)", copy.name);
+                                    drafts.insert(index + 1, copy);
+                                })) {
+                                    span(class = "icon") { i(class = "mdi mdi-content-copy"); }
+                                }
+                                button(class = "button is-small is-danger is-light", title = "Delete", onclick = edit_library(&drafts, {
+                                    let renaming = renaming.clone();
+                                    move |drafts| {
+                                        drafts.remove(index);
+                                        renaming.set(None);
+                                    }
+                                })) {
+                                    span(class = "icon") { i(class = "mdi mdi-delete"); }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    /// The state of the editor, which is saved as a new template.
+    pub current: EditorQuery,
+    /// Called with the state of a saved template to open it in the editor.
+    pub on_open: Callback<EditorQuery>,
+}
+
+/// Edits the saved templates and writes them back to local storage.
+fn edit_library(
+    drafts: &UseStateHandle<Vec<Draft>>,
+    f: impl Fn(&mut Vec<Draft>) + 'static,
+) -> Callback<MouseEvent> {
+    let drafts = drafts.clone();
+    Callback::from(move |_| {
+        let mut drafts_value = (*drafts).clone();
+        f(&mut drafts_value);
+        storage::save_library(&drafts_value);
+        drafts.set(drafts_value);
+    })
+}
+
+fn text_input(handle: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let handle = handle.clone();
+    Callback::from(move |event: InputEvent| {
+        let input: web_sys::HtmlInputElement = event.target_unchecked_into();
+        handle.set(input.value());
+    })
+}
@@ -0,0 +1,173 @@
+use defy::defy;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use super::kinds::KindLink;
+use crate::{
+    data::{Data, KindId, MappingName},
+    route::Route,
+};
+
+/// Shows all details of a mapping, including its parameters and aliases.
+#[function_component]
+pub fn MappingPage(props: &MappingPageProps) -> Html {
+    let mappings = props.schema.mappings.get(&props.kind);
+    let Some(mapping) = mappings.and_then(|mappings| mappings.get(&props.name)) else {
+        return defy! {
+            article(class = "message is-danger") {
+                div(class = "message-body") {
+                    + format!("There is no mapping called {} on {}.", props.name.0, props.kind.0);
+                }
+            }
+        };
+    };
+
+    let aliases: Vec<_> = props.schema.aliases_of(mapping).collect();
+
+    defy! {
+        nav(class = "breadcrumb") {
+            ul {
+                li {
+                    Link<Route>(to = Route::Kinds) { + "Kinds"; }
+                }
+                li {
+                    Link<Route>(to = Route::Kind { id: props.kind.0.clone() }) { + &props.kind.0; }
+                }
+                li(class = "is-active") {
+                    a { + mapping.name.last(); }
+                }
+            }
+        }
+
+        h1(class = "title is-family-monospace") {
+            + &mapping.name.0;
+        }
+        div(class = "tags") {
+            if mapping.is_implicit {
+                span(class = "tag is-info is-light", title = "Inserted automatically when the source kind has no mapping with the requested name") {
+                    + "implicit";
+                }
+            }
+            if mapping.mutable {
+                span(class = "tag is-success is-light") { + "mutable"; }
+            }
+        }
+
+        div(class = "content") {
+            p { + &mapping.help; }
+        }
+
+        table(class = "table") {
+            tbody {
+                tr {
+                    th { + "Source kind"; }
+                    td { KindLink(kind = mapping.source_kind.clone()); }
+                }
+                tr {
+                    th { + "Target kind"; }
+                    td { KindLink(kind = mapping.target_kind.clone()); }
+                }
+                tr {
+                    th { + "Implicit"; }
+                    td {
+                        if mapping.is_implicit {
+                            + "Yes. The server inserts this mapping automatically \
+                                when a name cannot be found on the source kind.";
+                        } else {
+                            + "No.";
+                        }
+                    }
+                }
+                tr {
+                    th { + "Mutable"; }
+                    td {
+                        if mapping.mutable {
+                            + "Yes. Templates rendered with renderContinuous are updated when this value changes.";
+                        } else {
+                            + "No. The value is only computed once in renderContinuous.";
+                        }
+                    }
+                }
+                tr {
+                    th { + "Source plugin"; }
+                    td { + mapping.metadata.source_plugin.as_deref().unwrap_or("unknown"); }
+                }
+                tr {
+                    th { + "Loaded from"; }
+                    td(class = "is-family-monospace") { + &mapping.origin; }
+                }
+                if let Some(alias_of) = &mapping.metadata.alias_of {
+                    tr {
+                        th { + "Alias of"; }
+                        td {
+                            MappingLink(kind = mapping.source_kind.clone(), name = MappingName(alias_of.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        h2(class = "title is-5") { + format!("Parameters ({})", mapping.parameters.len()); }
+        if !mapping.parameters.is_empty() {
+            table(class = "table is-fullwidth") {
+                thead {
+                    tr {
+                        th { + "Name"; }
+                        th { + "Kind"; }
+                        th { + "Optional"; }
+                        th { + "Multiple values"; }
+                    }
+                }
+                tbody {
+                    for param in &mapping.parameters {
+                        tr {
+                            td(class = "is-family-monospace") { + &param.name.0; }
+                            td { KindLink(kind = param.kind.clone()); }
+                            td { + if param.optional { "Yes" } else { "No" }; }
+                            td { + if param.multi { "Yes" } else { "No" }; }
+                        }
+                    }
+                }
+            }
+        }
+
+        h2(class = "title is-5") { + format!("Aliases ({})", aliases.len()); }
+        ul {
+            for alias in aliases {
+                li {
+                    MappingLink(kind = alias.source_kind.clone(), name = alias.name.clone());
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct MappingPageProps {
+    pub schema: Data,
+    pub kind: KindId,
+    pub name: MappingName,
+}
+
+/// A link to the page of a mapping, labelled with its fully qualified name.
+#[function_component]
+pub fn MappingLink(props: &MappingLinkProps) -> Html {
+    defy! {
+        Link<Route>(to = mapping_route(&props.kind, &props.name), classes = classes!["is-family-monospace"]) {
+            + &props.name.0;
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct MappingLinkProps {
+    pub kind: KindId,
+    pub name: MappingName,
+}
+
+pub fn mapping_route(kind: &KindId, name: &MappingName) -> Route {
+    Route::Mapping {
+        name: name.0.clone(),
+        kind: kind.0.clone(),
+    }
+}
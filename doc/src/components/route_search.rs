@@ -0,0 +1,117 @@
+use defy::defy;
+use yew::prelude::*;
+
+use super::EditableSelect;
+use crate::{
+    data::{Data, KindId},
+    document::Step,
+    pathfind, PluginFilter,
+};
+
+/// Routes with more mappings than this are not searched.
+const MAX_ROUTE_LEN: usize = 4;
+/// The number of routes listed at most.
+const MAX_ROUTES: usize = 20;
+
+/// Searches for chains of mappings from `source_kind` to a chosen target kind.
+#[function_component]
+pub fn RouteSearch(props: &Props) -> Html {
+    // `None` searches for any displayable kind
+    let target = use_state(|| props.target_kind.clone());
+
+    let mut options = vec![(None, "Any displayable kind".to_string())];
+    options.extend(
+        props
+            .schema
+            .kinds
+            .keys()
+            .map(|kind| (Some(kind.clone()), kind.0.clone())),
+    );
+    let default = options
+        .iter()
+        .position(|(kind, _)| *kind == *target)
+        .unwrap_or(0);
+
+    let routes = use_memo(
+        |(schema, source_kind, target, plugins)| {
+            pathfind::routes_to(
+                schema,
+                source_kind,
+                |kind| schema.admits(kind, target.as_ref()),
+                |mapping| {
+                    mapping.metadata.alias_of.is_none()
+                        && plugins.contains(mapping.metadata.source_plugin.as_ref())
+                },
+                MAX_ROUTE_LEN,
+                MAX_ROUTES,
+            )
+        },
+        (
+            props.schema.clone(),
+            props.source_kind.clone(),
+            (*target).clone(),
+            props.plugins.clone(),
+        ),
+    );
+
+    defy! {
+        div(class = "field") {
+            label(class = "label") {
+                + format!("Find a path from {} to", props.source_kind.0);
+            }
+            EditableSelect<Option<KindId>>(
+                options = options,
+                default = default,
+                on_change = Callback::from({
+                    let target = target.clone();
+                    move |kind| target.set(kind)
+                }),
+            );
+        }
+
+        if routes.is_empty() {
+            p(class = "help") {
+                + format!("No paths with at most {MAX_ROUTE_LEN} mappings");
+            }
+        }
+
+        for route in routes.iter().cloned() {
+            div(class = "level mb-1") {
+                div(class = "level-left") {
+                    div(class = "tags level-item") {
+                        for mapping in &route {
+                            span(class = "tag", title = mapping.help.clone()) {
+                                + mapping.name.last();
+                            }
+                        }
+                    }
+                    span(class = "level-item is-size-7") {
+                        + route.last().map_or("", |mapping| mapping.target_kind.0.as_str());
+                    }
+                }
+                div(class = "level-right") {
+                    button(class = "button is-small is-primary level-item", onclick = props.on_insert.reform({
+                        let schema = props.schema.clone();
+                        move |_| route.iter().map(|mapping| Step::new(&schema, mapping.clone())).collect()
+                    })) {
+                        span(class = "icon") {
+                            i(class = "mdi mdi-plus");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub schema: Data,
+    pub plugins: PluginFilter,
+    pub source_kind: KindId,
+    /// The initially searched kind, or `None` to search for any displayable kind.
+    #[prop_or_default]
+    pub target_kind: Option<KindId>,
+    /// Called with the steps of the chosen route.
+    pub on_insert: Callback<Vec<Step>>,
+}
@@ -0,0 +1,106 @@
+use defy::defy;
+use yew::prelude::*;
+
+use crate::data::{self, SourceSettings, SOURCE_LIST_HEADER};
+
+/// Edits the sources loaded in addition to `static/sources.txt`.
+#[function_component]
+pub fn SourceSettingsForm(props: &Props) -> Html {
+    let source_list = use_state(|| props.settings.source_list.clone());
+    let schema_json = use_state(|| props.settings.schema_json.clone());
+
+    let source_list_error = match source_list.trim() {
+        "" => None,
+        list => data::parse_source_list(list).err(),
+    };
+    // pasted schemas may be large, so they are only parsed again when edited
+    let schema_json_error = use_memo(
+        |json| match json.trim() {
+            "" => None,
+            json => data::parse_source(json, "pasted schema").err(),
+        },
+        (*schema_json).clone(),
+    );
+
+    let new_settings = SourceSettings {
+        source_list: (*source_list).clone(),
+        schema_json: (*schema_json).clone(),
+    };
+    let changed = new_settings != props.settings;
+
+    defy! {
+        div(class = "box") {
+            h2(class = "title is-5") {
+                + "Schema sources";
+            }
+
+            div(class = "field") {
+                label(class = "label") {
+                    + "Additional schema URLs";
+                }
+                div(class = "control") {
+                    textarea(
+                        class = classes!["textarea", "is-family-monospace", source_list_error.is_some().then_some("is-danger")],
+                        rows = "4",
+                        placeholder = format!("{SOURCE_LIST_HEADER}\n# comment\nhttps://example.com/schema.json"),
+                        value = (*source_list).clone(),
+                        oninput = textarea_input(&source_list),
+                    );
+                }
+                if let Some(err) = &source_list_error {
+                    p(class = "help is-danger") {
+                        + format!("{err:#}");
+                    }
+                }
+            }
+
+            div(class = "field") {
+                label(class = "label") {
+                    + "Schema JSON";
+                }
+                div(class = "control") {
+                    textarea(
+                        class = classes!["textarea", "is-family-monospace", schema_json_error.is_some().then_some("is-danger")],
+                        rows = "4",
+                        placeholder = "Paste a schema exported by Doc::export",
+                        value = (*schema_json).clone(),
+                        oninput = textarea_input(&schema_json),
+                    );
+                }
+                if let Some(err) = &*schema_json_error {
+                    p(class = "help is-danger") {
+                        + format!("{err:#}");
+                    }
+                }
+            }
+
+            p(class = "help block") {
+                + "These settings are saved in this browser. Applying them reloads all schemas.";
+            }
+
+            button(
+                class = "button is-primary",
+                disabled = !changed || source_list_error.is_some() || schema_json_error.is_some(),
+                onclick = props.on_save.reform(move |_| new_settings.clone()),
+            ) {
+                span(class = "icon") { i(class = "mdi mdi-content-save"); }
+                span { + "Apply"; }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    /// The currently applied settings.
+    pub settings: SourceSettings,
+    pub on_save: Callback<SourceSettings>,
+}
+
+fn textarea_input(handle: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let handle = handle.clone();
+    Callback::from(move |event: InputEvent| {
+        let textarea: web_sys::HtmlTextAreaElement = event.target_unchecked_into();
+        handle.set(textarea.value());
+    })
+}
@@ -0,0 +1,355 @@
+use defy::defy;
+use yew::prelude::*;
+use yew_hooks::use_clipboard;
+
+use super::{diagnostics::DiagnosticList, expr::CoalesceBuilder};
+use crate::{
+    data::{Data, KindId},
+    diagnostics,
+    document::{chain_string, Document, Element, NamePreference},
+    PluginFilter,
+};
+
+/// Edits a whole template consisting of raw text and placeholders.
+#[function_component]
+pub fn TemplateEditor(props: &Props) -> Html {
+    let initial = use_memo(
+        |initial| match initial.is_empty() {
+            true => Ok(Document::default()),
+            false => Document::parse(&props.schema, &props.source_kind, initial),
+        },
+        props.initial_template.clone(),
+    );
+
+    let document = use_state(|| initial.as_ref().clone().unwrap_or_default());
+    let selected = use_state(|| initial.is_err().then_some(0_usize));
+
+    let name_preference = use_state(NamePreference::default);
+    let template_string = document
+        .with_name_preference(&props.schema, *name_preference)
+        .template_string();
+    use_effect_with_deps(
+        {
+            let on_template_change = props.on_template_change.clone();
+            move |template_string: &String| on_template_change.emit(template_string.clone())
+        },
+        template_string.clone(),
+    );
+
+    let clipboard = use_clipboard();
+
+    // a template that fails to load is left in the draft for the user to fix
+    let draft = use_state(|| match &*initial {
+        Ok(_) => String::new(),
+        Err(_) => props.initial_template.clone(),
+    });
+    let draft_diagnostics = diagnostics::check(&props.schema, &props.source_kind, &draft);
+    let output_diagnostics =
+        diagnostics::check(&props.schema, &props.source_kind, &template_string);
+
+    let import_error = use_state(|| initial.as_ref().clone().err());
+    let import_template = Callback::from({
+        let document = document.clone();
+        let selected = selected.clone();
+        let draft = draft.clone();
+        let import_error = import_error.clone();
+        let schema = props.schema.clone();
+        let source_kind = props.source_kind.clone();
+
+        move |()| match Document::parse(&schema, &source_kind, &draft) {
+            Ok(parsed) => {
+                document.set(parsed);
+                selected.set(None);
+                import_error.set(None);
+            }
+            Err(err) => import_error.set(Some(err)),
+        }
+    });
+
+    let selected_chain = selected.and_then(|index| match document.elements.get(index) {
+        Some(Element::Expr(chain)) => Some((index, chain.clone())),
+        _ => None,
+    });
+
+    defy! {
+        div(class = "field has-addons") {
+            div(class = "control is-expanded") {
+                input(
+                    class = classes!["input", import_error.is_some().then_some("is-danger")],
+                    type = "text",
+                    placeholder = "Paste an existing template, e.g. Hello {player name}!",
+                    value = (*draft).clone(),
+                    oninput = Callback::from({
+                        let draft = draft.clone();
+                        move |event: InputEvent| {
+                            let input: web_sys::HtmlInputElement = event.target_unchecked_into();
+                            draft.set(input.value());
+                        }
+                    }),
+                );
+            }
+            div(class = "control") {
+                button(class = "button is-info", onclick = import_template.reform(|_| ())) {
+                    span(class = "icon") {
+                        i(class = "mdi mdi-import");
+                    }
+                    span {
+                        + "Load";
+                    }
+                }
+            }
+        }
+        if let Some(err) = &*import_error {
+            pre(class = "help is-danger") {
+                + err;
+            }
+        }
+        DiagnosticList(buf = (*draft).clone(), diagnostics = draft_diagnostics);
+
+        div(class = "field has-addons") {
+            div(class = "control") {
+                div(class = "select") {
+                    select(
+                        title = "Names written for mappings with aliases",
+                        onchange = Callback::from({
+                            let name_preference = name_preference.clone();
+                            move |event: Event| {
+                                let select: web_sys::HtmlSelectElement = event.target_unchecked_into();
+                                name_preference.set(match select.value().as_str() {
+                                    "canonical" => NamePreference::Canonical,
+                                    "alias" => NamePreference::Alias,
+                                    _ => NamePreference::AsChosen,
+                                });
+                            }
+                        }),
+                    ) {
+                        option(value = "chosen", selected = *name_preference == NamePreference::AsChosen) {
+                            + "Names as chosen";
+                        }
+                        option(value = "canonical", selected = *name_preference == NamePreference::Canonical) {
+                            + "Prefer canonical names";
+                        }
+                        option(value = "alias", selected = *name_preference == NamePreference::Alias) {
+                            + "Prefer aliases";
+                        }
+                    }
+                }
+            }
+            div(class = "control is-expanded") {
+                input(
+                    class = "input",
+                    type = "text", readonly = true,
+                    value = template_string.clone(),
+                );
+            }
+            div(class = "control") {
+                button(class = "button", onclick = Callback::from({
+                    let clipboard = clipboard.clone();
+                    let template_string = template_string.clone();
+                    move |_| clipboard.write_text(template_string.clone())
+                })) {
+                    span(class = "icon") {
+                        i(class = "mdi mdi-content-copy");
+                    }
+                    span {
+                        + "Copy";
+                    }
+                }
+            }
+        }
+
+        DiagnosticList(buf = template_string.clone(), diagnostics = output_diagnostics);
+
+        div(class = "box") {
+            h2(class = "heading") { + "Template"; }
+
+            for (index, element) in document.elements.iter().enumerate() {
+                div(class = "level mb-2") {
+                    div(class = "level-item is-flex-grow-1 is-justify-content-flex-start") {
+                        match element {
+                            Element::RawText(text) => {
+                                RawTextInput(
+                                    text = text.clone(),
+                                    on_change = Callback::from({
+                                        let document = document.clone();
+                                        move |text| {
+                                            let mut document_value = (*document).clone();
+                                            document_value.elements[index] = Element::RawText(text);
+                                            document.set(document_value);
+                                        }
+                                    }),
+                                    on_insert = Callback::from({
+                                        let document = document.clone();
+                                        let selected = selected.clone();
+                                        move |offset| {
+                                            let mut document_value = (*document).clone();
+                                            selected.set(Some(document_value.insert_expr(index, offset)));
+                                            document.set(document_value);
+                                        }
+                                    }),
+                                );
+                            }
+                            Element::Expr(chain) => {
+                                button(
+                                    class = classes!["button", "is-family-monospace", (*selected == Some(index)).then_some("is-link")],
+                                    onclick = state_select(&selected, index),
+                                ) {
+                                    + format!("{{{}}}", chain_string(chain));
+                                }
+                            }
+                        }
+                    }
+
+                    div(class = "level-right") {
+                        div(class = "buttons has-addons level-item") {
+                            button(class = "button is-small", disabled = index == 0, onclick = edit_document(&document, &selected, move |document| {
+                                document.move_up(index);
+                                None
+                            })) {
+                                span(class = "icon") { i(class = "mdi mdi-arrow-up"); }
+                            }
+                            button(class = "button is-small", disabled = index + 1 == document.elements.len(), onclick = edit_document(&document, &selected, move |document| {
+                                document.move_up(index + 1);
+                                None
+                            })) {
+                                span(class = "icon") { i(class = "mdi mdi-arrow-down"); }
+                            }
+                            button(class = "button is-small is-danger is-light", onclick = edit_document(&document, &selected, move |document| {
+                                document.remove(index);
+                                None
+                            })) {
+                                span(class = "icon") { i(class = "mdi mdi-delete"); }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div(class = "buttons") {
+                button(class = "button is-small", onclick = edit_document(&document, &selected, |document| {
+                    Some(document.push_text())
+                })) {
+                    span(class = "icon") { i(class = "mdi mdi-text"); }
+                    span { + "Add text"; }
+                }
+                button(class = "button is-small", onclick = edit_document(&document, &selected, |document| {
+                    let index = document.elements.len();
+                    Some(document.insert_expr(index, 0))
+                })) {
+                    span(class = "icon") { i(class = "mdi mdi-code-braces"); }
+                    span { + "Add placeholder"; }
+                }
+            }
+        }
+
+        if let Some((index, chain)) = selected_chain {
+            label(class = "label is-medium") {
+                + "Build your expression";
+            }
+
+            CoalesceBuilder(
+                schema = props.schema.clone(),
+                root_kind = props.source_kind.clone(),
+                plugins = props.plugins.clone(),
+                chain = chain,
+                on_change = Callback::from({
+                    let document = document.clone();
+                    move |chain| {
+                        let mut document_value = (*document).clone();
+                        document_value.elements[index] = Element::Expr(chain);
+                        document.set(document_value);
+                    }
+                }),
+            );
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub source_kind: KindId,
+    pub schema: Data,
+    pub plugins: PluginFilter,
+    /// The template string to load on creation.
+    #[prop_or_default]
+    pub initial_template: String,
+    /// Called with the template string whenever it changes.
+    #[prop_or_default]
+    pub on_template_change: Callback<String>,
+}
+
+fn edit_document(
+    document: &UseStateHandle<Document>,
+    selected: &UseStateHandle<Option<usize>>,
+    f: impl Fn(&mut Document) -> Option<usize> + 'static,
+) -> Callback<MouseEvent> {
+    // indices may shift, so the selection is replaced with the returned index
+    let document = document.clone();
+    let selected = selected.clone();
+    Callback::from(move |_| {
+        let mut document_value = (*document).clone();
+        selected.set(f(&mut document_value));
+        document.set(document_value);
+    })
+}
+
+fn state_select(handle: &UseStateHandle<Option<usize>>, index: usize) -> Callback<MouseEvent> {
+    let handle = handle.clone();
+    Callback::from(move |_| handle.set(Some(index)))
+}
+
+#[function_component]
+fn RawTextInput(props: &RawTextProps) -> Html {
+    let textarea = use_node_ref();
+
+    let oninput = props.on_change.reform(|event: InputEvent| {
+        let textarea: web_sys::HtmlTextAreaElement = event.target_unchecked_into();
+        textarea.value()
+    });
+
+    let insert_at_cursor = Callback::from({
+        let textarea = textarea.clone();
+        let on_insert = props.on_insert.clone();
+        let text = props.text.clone();
+        move |_: MouseEvent| {
+            let textarea = textarea
+                .cast::<web_sys::HtmlTextAreaElement>()
+                .expect("raw text input is a textarea");
+            let cursor = textarea.selection_start().ok().flatten().unwrap_or(0);
+            on_insert.emit(utf16_to_byte_offset(&text, cursor as usize));
+        }
+    });
+
+    defy! {
+        div(class = "field has-addons is-flex-grow-1") {
+            div(class = "control is-expanded") {
+                textarea(class = "textarea", rows = "1", value = props.text.clone(), oninput = oninput, ref = textarea.clone());
+            }
+            div(class = "control") {
+                button(class = "button", title = "Insert placeholder at cursor", onclick = insert_at_cursor) {
+                    span(class = "icon") { i(class = "mdi mdi-code-braces"); }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct RawTextProps {
+    text: String,
+    on_change: Callback<String>,
+    /// Called with the byte offset of the cursor when a placeholder should be inserted.
+    on_insert: Callback<usize>,
+}
+
+/// Converts a DOM selection offset, which counts UTF-16 code units, into a byte offset in `text`.
+fn utf16_to_byte_offset(text: &str, utf16_offset: usize) -> usize {
+    let mut units = 0;
+    for (byte, c) in text.char_indices() {
+        if units >= utf16_offset {
+            return byte;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
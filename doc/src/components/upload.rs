@@ -0,0 +1,94 @@
+use defy::defy;
+use gloo::file::{futures::read_as_text, File};
+use yew::{platform::spawn_local, prelude::*};
+
+/// A schema file read from the user's device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Upload {
+    pub file_name: String,
+    /// The file contents, or the reason it could not be read.
+    pub contents: Result<String, String>,
+}
+
+/// Loads schema JSON files exported by `Doc::export` through a file picker or drag-and-drop.
+#[function_component]
+pub fn SchemaUpload(props: &Props) -> Html {
+    let dragging = use_state(|| false);
+
+    let read_files = Callback::from({
+        let on_upload = props.on_upload.clone();
+        move |files: Vec<File>| {
+            let on_upload = on_upload.clone();
+            spawn_local(async move {
+                let mut uploads = Vec::new();
+                for file in files {
+                    uploads.push(Upload {
+                        file_name: file.name(),
+                        contents: read_as_text(&file).await.map_err(|err| err.to_string()),
+                    });
+                }
+                on_upload.emit(uploads);
+            });
+        }
+    });
+
+    let onchange = read_files.reform(|event: Event| {
+        let input: web_sys::HtmlInputElement = event.target_unchecked_into();
+        let files = input
+            .files()
+            .map(|files| gloo::file::FileList::from(files).to_vec());
+        // allow choosing the same files again
+        input.set_value("");
+        files.unwrap_or_default()
+    });
+
+    let ondrop = Callback::from({
+        let dragging = dragging.clone();
+        let read_files = read_files.clone();
+        move |event: DragEvent| {
+            event.prevent_default();
+            dragging.set(false);
+            let files = event
+                .data_transfer()
+                .and_then(|transfer| transfer.files())
+                .map(|files| gloo::file::FileList::from(files).to_vec());
+            read_files.emit(files.unwrap_or_default());
+        }
+    });
+
+    defy! {
+        div(
+            class = classes!["file", "is-boxed", "is-centered", dragging.then_some("is-primary")],
+            ondragover = Callback::from({
+                let dragging = dragging.clone();
+                move |event: DragEvent| {
+                    // required for the drop event to fire
+                    event.prevent_default();
+                    dragging.set(true);
+                }
+            }),
+            ondragleave = Callback::from({
+                let dragging = dragging.clone();
+                move |_: DragEvent| dragging.set(false)
+            }),
+            ondrop = ondrop,
+        ) {
+            label(class = "file-label") {
+                input(class = "file-input", type = "file", multiple = true, accept = ".json,application/json", onchange = onchange);
+                span(class = "file-cta") {
+                    span(class = "file-icon") {
+                        i(class = "mdi mdi-upload");
+                    }
+                    span(class = "file-label") {
+                        + "Choose or drop schema files";
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub on_upload: Callback<Vec<Upload>>,
+}
@@ -1,227 +1,208 @@
-use std::{
-    collections::{BTreeMap, BTreeSet},
-    ops,
-    rc::Rc,
-};
+//! Loading schemas into the editor.
+//!
+//! The schema model itself is defined in the `infoapi-schema` crate.
 
+#[cfg(any(feature = "lint", feature = "ssg"))]
 use anyhow::Context as _;
-use futures::{stream::FuturesUnordered, StreamExt};
-use gloo::net::http;
-use serde::Deserialize;
-
-pub const SOURCE_LIST_HEADER: &str = "=== InfoAPI schema list ===";
-
-async fn fetch_sources() -> anyhow::Result<Vec<String>> {
-    let resp = http::Request::get("static/sources.txt")
-        .send()
-        .await
-        .context("HTTP")?
-        .text()
-        .await
-        .context("parse result")?;
-
-    let mut lines = resp.split('\n');
-
-    let Some(header) = lines.next() else {
-        anyhow::bail!("invalid empty response")
-    };
-    anyhow::ensure!(
-        header == SOURCE_LIST_HEADER,
-        "sources.txt is not a schema list"
-    );
-
-    Ok(lines
-        .filter(|line| !line.is_empty() && !line.starts_with('#'))
-        .map(str::to_string)
-        .collect())
-}
-
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
-pub struct KindId(pub String);
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct KindDef {
-    #[serde(default)]
-    pub help: String,
-    #[serde(default)]
-    pub can_display: bool,
-    #[serde(default)]
-    pub metadata: KnownKindMetadata,
-}
 
-#[derive(Debug, Default, Deserialize)]
-pub struct KnownKindMetadata {
-    #[serde(default, rename = "infoapi/is-root")]
-    pub is_root: bool,
-    #[serde(default, rename = "infoapi:browser/template-name")]
-    pub template_name: Option<String>,
-    #[serde(default, rename = "infoapi/source-plugin")]
-    pub source_plugin: Option<String>,
-}
+pub use infoapi_schema::{
+    parse_source, All, KindId, MappingDef, MappingName, ParamDef, QualifiedRef, FQN_SEPARATOR,
+};
+#[cfg(feature = "web")]
+pub use infoapi_schema::{SourceSchema, FORMAT_VERSION};
+
+#[cfg(feature = "web")]
+pub use web::*;
+
+/// Loads and merges schema files exported by `Doc::export` for the native tools.
+///
+/// Unlike `all`, a file that cannot be read fails the whole schema.
+/// Skipped parts and conflicting definitions are reported as warnings on stderr.
+#[cfg(any(feature = "lint", feature = "ssg"))]
+pub fn load_files(paths: &[String]) -> anyhow::Result<All> {
+    let mut schema = All::default();
+    for path in paths {
+        let json = std::fs::read_to_string(path).with_context(|| format!("read schema {path}"))?;
+        let source = parse_source(&json, path).with_context(|| format!("load schema {path}"))?;
+        schema.extend([Ok(source)]);
+    }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
-pub struct MappingName(pub String);
-impl MappingName {
-    pub fn last(&self) -> &str {
-        self.0.split(':').next_back().expect("split is nonempty")
+    for err in &schema.errors {
+        eprintln!("warning: {err:#}");
+    }
+    for conflict in &schema.conflicts {
+        eprintln!(
+            "warning: {} from {} is redefined in {}",
+            conflict.subject, conflict.old_origin, conflict.new_origin
+        );
     }
 
-    pub fn minify<'t>(&'t self, others: impl Iterator<Item = &'t MappingName>) -> String {
-        let my_last = self.last();
-        let mut collisions: Vec<Vec<_>> = others
-            .filter(|other| other.0 != self.0 && other.last() == my_last)
-            .map(|other| other.0.split(':').collect())
-            .collect();
+    Ok(schema)
+}
 
-        let mut my_pieces: Vec<_> = self.0.split(':').collect();
-        _ = my_pieces.pop();
-        let mut use_prefixes = 0;
-
-        fn has_subprefix(haystack: &[&str], needle: &[&str]) -> bool {
-            let mut needle = needle.iter().copied().peekable();
-            for &haystack_part in haystack {
-                let Some(&needle_part) = needle.peek() else {
-                    // needle is fully removed
-                    return true;
-                };
-                if haystack_part == needle_part {
-                    _ = needle.next();
-                }
-            }
+/// Fetching schemas in the browser.
+#[cfg(feature = "web")]
+mod web {
+    use std::{ops, rc::Rc, sync::Arc};
 
-            needle.next().is_none()
-        }
+    use anyhow::Context as _;
+    use futures::{stream::FuturesUnordered, StreamExt};
+    use gloo::net::http;
+    use serde::{Deserialize, Serialize};
 
-        while !collisions.is_empty() {
-            use_prefixes += 1;
-            if use_prefixes > my_pieces.len() {
-                // subset of another, cannot fully qualify
-                break;
-            }
+    use super::{parse_source, All, SourceSchema};
 
-            collisions.retain(|other| {
-                has_subprefix(&other[..other.len() - 1], &my_pieces[..use_prefixes])
-            })
-        }
+    pub const SOURCE_LIST_HEADER: &str = "=== InfoAPI schema list ===";
 
-        let mut out = my_pieces;
-        out.truncate(use_prefixes);
-        out.push(my_last);
-        out.join(":")
+    async fn fetch_sources() -> anyhow::Result<Vec<String>> {
+        let resp = http::Request::get("static/sources.txt")
+            .send()
+            .await
+            .context("HTTP")?
+            .text()
+            .await
+            .context("parse result")?;
+
+        parse_source_list(&resp).context("sources.txt is not a schema list")
     }
-}
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct MappingDef {
-    pub source_kind: KindId,
-    pub target_kind: KindId,
-    pub name: MappingName,
-    pub is_implicit: bool,
-    pub parameters: Vec<ParamDef>,
-    pub mutable: bool,
-    pub help: String,
-    #[serde(default)]
-    pub metadata: KnownMappingMetadata,
-}
+    /// Parses a list of schema URLs starting with `SOURCE_LIST_HEADER`, skipping empty lines and `#` comments.
+    pub fn parse_source_list(list: &str) -> anyhow::Result<Vec<String>> {
+        let mut lines = list.split('\n').map(str::trim);
+
+        let Some(header) = lines.next() else {
+            anyhow::bail!("invalid empty response")
+        };
+        anyhow::ensure!(
+            header == SOURCE_LIST_HEADER,
+            "the first line must be {SOURCE_LIST_HEADER}"
+        );
+
+        Ok(lines
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect())
+    }
 
-#[derive(Debug, Default, Deserialize)]
-pub struct KnownMappingMetadata {
-    #[serde(default, rename = "infoapi/source-plugin")]
-    pub source_plugin: Option<String>,
-    #[serde(default, rename = "infoapi/alias-of")]
-    pub alias_of: Option<String>,
-}
+    /// Sources configured by the user in addition to `static/sources.txt`.
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    pub struct SourceSettings {
+        /// A list of schema URLs in the same format as `static/sources.txt`, or empty.
+        #[serde(default)]
+        pub source_list: String,
+        /// A schema exported by `Doc::export`, or empty.
+        #[serde(default)]
+        pub schema_json: String,
+    }
 
-#[derive(Debug, Deserialize)]
-pub struct ParamName(pub String);
+    #[derive(Clone)]
+    pub struct Data(Rc<All>);
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ParamDef {
-    pub name: ParamName,
-    pub kind: KindId,
-    pub multi: bool,
-    pub optional: bool,
-}
+    impl PartialEq for Data {
+        fn eq(&self, other: &Self) -> bool {
+            Rc::ptr_eq(&self.0, &other.0)
+        }
+    }
 
-#[derive(Deserialize)]
-pub struct SourceSchema {
-    kinds: BTreeMap<KindId, KindDef>,
-    mappings: Vec<MappingDef>,
-}
+    impl ops::Deref for Data {
+        type Target = All;
 
-#[derive(Clone)]
-pub struct Data(Rc<All>);
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
 
-impl PartialEq for Data {
-    fn eq(&self, other: &Self) -> bool {
-        Rc::ptr_eq(&self.0, &other.0)
+    impl From<All> for Data {
+        fn from(all: All) -> Self {
+            Self(Rc::new(all))
+        }
     }
-}
 
-impl ops::Deref for Data {
-    type Target = All;
+    impl Data {
+        /// Merges more sources into a copy of this schema.
+        pub fn extended(
+            &self,
+            sources: impl IntoIterator<Item = anyhow::Result<SourceSchema>>,
+        ) -> Self {
+            let mut all = (*self.0).clone();
+            all.extend(sources);
+            Self(Rc::new(all))
+        }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    async fn fetch_source(source_url: &str) -> anyhow::Result<SourceSchema> {
+        let json = http::Request::get(source_url)
+            .send()
+            .await
+            .context("HTTP")?
+            .text()
+            .await
+            .context("parse result")?;
+        parse_source(&json, source_url)
     }
-}
 
-#[derive(Default)]
-pub struct All {
-    pub kinds: BTreeMap<KindId, KindDef>,
-    pub mappings: BTreeMap<KindId, BTreeMap<MappingName, Rc<MappingDef>>>,
-    pub known_plugins: BTreeSet<String>,
-    pub errors: Vec<anyhow::Error>,
-}
+    /// Fetches all sources listed in `static/sources.txt` and `settings`.
+    ///
+    /// Failures are recorded in `All::errors` so that schemas can still be loaded from other places.
+    pub async fn all(settings: Rc<SourceSettings>) -> Data {
+        let mut schema = All::default();
 
-impl Extend<anyhow::Result<SourceSchema>> for All {
-    fn extend<T: IntoIterator<Item = anyhow::Result<SourceSchema>>>(&mut self, iter: T) {
-        for schema in iter {
-            match schema {
-                Ok(schema) => {
-                    self.kinds.extend(schema.kinds);
-                    for mapping in schema.mappings {
-                        if let Some(plugin) = &mapping.metadata.source_plugin {
-                            self.known_plugins.insert(plugin.clone());
-                        }
-
-                        self.mappings
-                            .entry(mapping.source_kind.clone())
-                            .or_default()
-                            .insert(mapping.name.clone(), Rc::new(mapping));
-                    }
-                }
-                Err(err) => self.errors.push(err),
+        let mut sources = Vec::new();
+        match fetch_sources().await.context("fetch sources list") {
+            Ok(list) => sources.extend(list),
+            Err(err) => schema.errors.push(Arc::new(err)),
+        }
+        if !settings.source_list.trim().is_empty() {
+            match parse_source_list(&settings.source_list).context("parse additional sources list")
+            {
+                Ok(list) => sources.extend(list),
+                Err(err) => schema.errors.push(Arc::new(err)),
             }
         }
+
+        let futures: FuturesUnordered<_> = sources
+            .into_iter()
+            .map(|source| async move {
+                fetch_source(&source)
+                    .await
+                    .with_context(|| format!("fetch source from {source}"))
+            })
+            .collect();
+        schema.extend(futures.collect::<Vec<_>>().await);
+
+        if !settings.schema_json.trim().is_empty() {
+            schema
+                .extend([parse_source(&settings.schema_json, "pasted schema")
+                    .context("load pasted schema")]);
+        }
+
+        Data(Rc::new(schema))
     }
-}
 
-async fn fetch_source(source_url: &str) -> anyhow::Result<SourceSchema> {
-    http::Request::get(source_url)
-        .send()
-        .await
-        .context("HTTP")?
-        .json::<SourceSchema>()
-        .await
-        .context("parse results as JSON")
-}
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn parse_source_list_skips_comments() {
+            let list = format!(
+                "{SOURCE_LIST_HEADER}\r\n# comment\n\n  https://example.com/a.json  \nhttps://example.com/b.json"
+            );
+            assert_eq!(
+                parse_source_list(&list).unwrap(),
+                ["https://example.com/a.json", "https://example.com/b.json"]
+            );
+            assert!(parse_source_list(SOURCE_LIST_HEADER).unwrap().is_empty());
+        }
 
-pub async fn all() -> anyhow::Result<Data> {
-    let sources = fetch_sources().await.context("fetch sources list")?;
-
-    let futures: FuturesUnordered<_> = sources
-        .into_iter()
-        .map(|source| async move {
-            fetch_source(&source)
-                .await
-                .with_context(|| format!("fetch source from {source}"))
-        })
-        .collect();
-    let schema: All = futures.collect().await;
-    anyhow::ensure!(!schema.kinds.is_empty(), "all sources cannot be loaded");
-    Ok(Data(Rc::new(schema)))
+        #[test]
+        fn parse_source_list_requires_header() {
+            let err = parse_source_list("https://example.com/a.json").unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("the first line must be {SOURCE_LIST_HEADER}")
+            );
+            assert!(parse_source_list("").is_err());
+        }
+    }
 }
@@ -0,0 +1,434 @@
+//! Reports problems in a template string before it is used on the server.
+
+#[cfg(feature = "web")]
+use std::fmt;
+use std::ops::Range;
+
+#[cfg(feature = "web")]
+use crate::parser::carets;
+use crate::{
+    ast,
+    data::{All, KindId, MappingDef},
+    resolve::{self, ResolveError, UnmatchedArg},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// The byte range in the template that the diagnostic refers to.
+    pub span: Range<usize>,
+}
+
+impl Diagnostic {
+    fn error(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+        }
+    }
+
+    fn warning(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Formats the diagnostic with carets under `buf`, in the same format as `ParseException`.
+    #[cfg(feature = "web")]
+    pub fn display<'t>(&'t self, buf: &'t str) -> impl fmt::Display + 't {
+        struct Display<'t>(&'t Diagnostic, &'t str);
+
+        impl fmt::Display for Display<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let Self(diagnostic, buf) = self;
+                write!(
+                    f,
+                    "{}\n{buf}\n{}",
+                    diagnostic.message,
+                    carets(buf, diagnostic.span.clone())
+                )
+            }
+        }
+
+        Display(self, buf)
+    }
+}
+
+/// Parses `buf` and checks every expression against `schema` with `source_kind` as the root kind.
+pub fn check(schema: &All, source_kind: &KindId, buf: &str) -> Vec<Diagnostic> {
+    let template = match ast::parse(buf) {
+        Ok(template) => template,
+        Err(err) => return vec![Diagnostic::error(err.why.clone(), err.start..err.end)],
+    };
+
+    let mut checker = Checker {
+        schema,
+        source_kind,
+        diagnostics: Vec::new(),
+    };
+    for element in &template.elements {
+        if let ast::Element::Expr(expr) = element {
+            checker.check_expr(expr, None);
+        }
+    }
+    checker.diagnostics
+}
+
+struct Checker<'t> {
+    schema: &'t All,
+    source_kind: &'t KindId,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    /// Checks each alternative of a coalescence chain.
+    ///
+    /// Top-level expressions (`expect_kind` is `None`) must resolve to a displayable kind,
+    /// while argument expressions must resolve to the parameter kind.
+    fn check_expr(&mut self, expr: &ast::Expr, expect_kind: Option<&KindId>) {
+        let mut next = Some(expr);
+        while let Some(expr) = next {
+            self.check_info_expr(&expr.main, expect_kind);
+            next = expr.else_.as_deref();
+        }
+    }
+
+    fn check_info_expr(&mut self, expr: &ast::InfoExpr, expect_kind: Option<&KindId>) {
+        for arg in expr
+            .calls
+            .iter()
+            .flat_map(|call| call.args.iter().flatten())
+        {
+            if let (Some((prefix, span)), ast::ArgValue::Expr(value)) =
+                (&arg.skipped_prefix, &arg.value)
+            {
+                let called = value.main.calls.first().map(|call| call.name.to_string());
+                self.diagnostics.push(Diagnostic::warning(
+                    format!(
+                        "the server skips `{prefix}` because it is not a number, so `{}` is called instead",
+                        called.unwrap_or_default()
+                    ),
+                    span.clone(),
+                ));
+            }
+        }
+
+        let schema = self.schema;
+        let result = resolve::resolve_info_expr(schema, self.source_kind, expr, |kind| {
+            schema.admits(kind, expect_kind)
+        });
+
+        let hops = match result {
+            Ok(hops) => hops,
+            Err(err) => {
+                let span = match &err {
+                    ResolveError::UnknownMapping { call, .. }
+                    | ResolveError::Ambiguous { call, .. } => expr.calls[*call].name_span.clone(),
+                    ResolveError::Unadmitted { .. } => {
+                        let (Some(first), Some(last)) = (expr.calls.first(), expr.calls.last())
+                        else {
+                            return;
+                        };
+                        first.name_span.start..last.name_span.end
+                    }
+                };
+                let message = match (&err, expect_kind) {
+                    (ResolveError::Unadmitted { kind }, Some(expect_kind)) => format!(
+                        "expected {}, but the expression resolves to {}",
+                        expect_kind.0, kind.0
+                    ),
+                    (ResolveError::Unadmitted { kind }, None) => {
+                        format!("{} cannot be displayed", kind.0)
+                    }
+                    _ => err.to_string(),
+                };
+                self.diagnostics.push(Diagnostic::error(message, span));
+                return;
+            }
+        };
+
+        for hop in hops {
+            if let Some(call) = hop.call {
+                self.check_args(&expr.calls[call], &hop.mapping);
+            }
+        }
+    }
+
+    /// Checks the arguments of a call against the parameters they are passed to on the server.
+    fn check_args(&mut self, call: &ast::MappingCall, mapping: &MappingDef) {
+        let mapping_name = &mapping.name.0;
+        let params = &mapping.parameters;
+        let args = call.args.as_deref().unwrap_or_default();
+        let matched =
+            resolve::match_args_to_params(params, args.iter().map(|arg| arg.name.as_deref()));
+
+        for (i, (arg, index)) in args.iter().zip(&matched.args).enumerate() {
+            let index = match index {
+                Ok(index) => *index,
+                Err(UnmatchedArg::UnknownName) => {
+                    let name = arg.name.as_deref().unwrap_or_default();
+                    self.diagnostics.push(Diagnostic::warning(
+                        format!("`{mapping_name}` has no parameter called `{name}`, so this argument is ignored"),
+                        arg.span.clone(),
+                    ));
+                    continue;
+                }
+                Err(UnmatchedArg::TooMany) => {
+                    self.diagnostics.push(Diagnostic::error(
+                        format!("too many arguments for `{mapping_name}`"),
+                        arg.span.clone(),
+                    ));
+                    continue;
+                }
+            };
+
+            let param = &params[index];
+            if matched.params[index] != Some(i) {
+                let message = match param.multi {
+                    true => format!(
+                        "the server only uses the last value for `{}`, so this argument is ignored",
+                        param.name.0
+                    ),
+                    false => format!(
+                        "`{}` is passed again later, so this argument is ignored",
+                        param.name.0
+                    ),
+                };
+                self.diagnostics
+                    .push(Diagnostic::warning(message, arg.span.clone()));
+            }
+
+            // the server resolves overwritten arguments too, so they are checked the same way
+            match &arg.value {
+                ast::ArgValue::Expr(expr) => self.check_expr(expr, Some(&param.kind)),
+                ast::ArgValue::Json(value) => {
+                    if let Some(message) = literal_mismatch(&param.kind, &value.json) {
+                        self.diagnostics.push(Diagnostic::error(
+                            format!("parameter `{}` {message}", param.name.0),
+                            arg.span.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (param, arg) in params.iter().zip(&matched.params) {
+            if arg.is_none() && !param.optional {
+                self.diagnostics.push(Diagnostic::warning(
+                    format!(
+                        "missing required parameter `{}` for `{mapping_name}`",
+                        param.name.0
+                    ),
+                    call.name_span.clone(),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks whether a JSON literal can be passed to a parameter of `kind`.
+fn literal_mismatch(kind: &KindId, json: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let ok = match kind.0.as_str() {
+        KindId::STRING => value.is_string(),
+        KindId::INTEGER => value.is_i64() || value.is_u64(),
+        KindId::FLOAT => value.is_number(),
+        KindId::BOOL => value.is_boolean(),
+        _ => {
+            return Some(format!(
+                "expects {}, which cannot be passed as a literal",
+                kind.0
+            ))
+        }
+    };
+
+    (!ok).then(|| format!("expects {}, but got {json}", kind.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parser::carets,
+        testing::{mapping, param, schema, with_params},
+    };
+
+    const ROOT: &str = "test/root";
+    const PLAYER: &str = "test/player";
+
+    fn fixture() -> All {
+        schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![
+                with_params(
+                    mapping(ROOT, "test:greet", KindId::STRING),
+                    vec![param("target", KindId::STRING, false, false)],
+                ),
+                mapping(ROOT, "test:xp", KindId::STRING),
+                with_params(
+                    mapping(ROOT, "test:join", KindId::STRING),
+                    vec![
+                        param("first", KindId::STRING, false, false),
+                        param("rest", KindId::STRING, true, true),
+                    ],
+                ),
+                mapping(ROOT, "test:player", PLAYER),
+                mapping(ROOT, "first:name", KindId::STRING),
+                mapping(ROOT, "second:name", KindId::STRING),
+            ],
+        )
+    }
+
+    /// Renders each diagnostic as its message followed by the carets under `buf`.
+    fn render(buf: &str) -> Vec<String> {
+        check(&fixture(), &KindId(ROOT.into()), buf)
+            .into_iter()
+            .map(|diagnostic| format!("{}\n{}", diagnostic.message, carets(buf, diagnostic.span)))
+            .collect()
+    }
+
+    #[test]
+    fn skipped_number_prefix_warns() {
+        let diagnostics = check(&fixture(), &KindId(ROOT.into()), "{greet(exp)}");
+        assert_eq!(
+            diagnostics,
+            [Diagnostic::warning(
+                "the server skips `e` because it is not a number, so `xp` is called instead",
+                7..8,
+            )]
+        );
+    }
+
+    #[test]
+    fn quoted_arg_does_not_warn() {
+        let diagnostics = check(&fixture(), &KindId(ROOT.into()), r#"{greet("exp")} {xp}"#);
+        assert_eq!(diagnostics, []);
+    }
+
+    #[test]
+    fn multi_param_uses_last_value() {
+        let diagnostics = check(
+            &fixture(),
+            &KindId(ROOT.into()),
+            r#"{join("a", "b", rest="c")}"#,
+        );
+        assert_eq!(
+            diagnostics,
+            [Diagnostic::warning(
+                "the server only uses the last value for `rest`, so this argument is ignored",
+                11..14,
+            )]
+        );
+    }
+
+    #[test]
+    fn unmatched_args() {
+        let diagnostics = check(
+            &fixture(),
+            &KindId(ROOT.into()),
+            r#"{join(rest="a", other="b", "c", "d")}"#,
+        );
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic::warning(
+                    "`test:join` has no parameter called `other`, so this argument is ignored",
+                    16..25,
+                ),
+                Diagnostic::error("too many arguments for `test:join`", 32..35),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_mapping() {
+        let buf = "{xp} {missing(1)}";
+        let diagnostics = check(&fixture(), &KindId(ROOT.into()), buf);
+        assert_eq!(
+            diagnostics,
+            [Diagnostic::error(
+                "no mapping called `missing` for test/root",
+                6..13
+            )]
+        );
+        assert_eq!(
+            render(buf),
+            ["no mapping called `missing` for test/root\n      ^^^^^^^"]
+        );
+    }
+
+    #[test]
+    fn ambiguous_mapping() {
+        let buf = "{name} {first:name}";
+        let diagnostics = check(&fixture(), &KindId(ROOT.into()), buf);
+        assert_eq!(
+            diagnostics,
+            [Diagnostic::error(
+                "`name` for test/root is ambiguous between first:name, second:name",
+                1..5
+            )]
+        );
+        assert_eq!(
+            render(buf),
+            ["`name` for test/root is ambiguous between first:name, second:name\n ^^^^"]
+        );
+    }
+
+    #[test]
+    fn undisplayable_kind() {
+        let buf = "{player}";
+        let diagnostics = check(&fixture(), &KindId(ROOT.into()), buf);
+        assert_eq!(
+            diagnostics,
+            [Diagnostic::error("test/player cannot be displayed", 1..7)]
+        );
+        assert_eq!(render(buf), ["test/player cannot be displayed\n ^^^^^^"]);
+    }
+
+    #[test]
+    fn arg_kind_mismatch() {
+        let buf = "{greet(player)} {greet(1)}";
+        let diagnostics = check(&fixture(), &KindId(ROOT.into()), buf);
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic::error(
+                    "expected infoapi/string, but the expression resolves to test/player",
+                    7..13
+                ),
+                Diagnostic::error(
+                    "parameter `target` expects infoapi/string, but got 1",
+                    23..24
+                ),
+            ]
+        );
+        assert_eq!(
+            render(buf),
+            [
+                "expected infoapi/string, but the expression resolves to test/player\n       ^^^^^^",
+                "parameter `target` expects infoapi/string, but got 1\n                       ^",
+            ]
+        );
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn display_with_carets() {
+        let buf = "{greet(player)}";
+        let diagnostics = check(&fixture(), &KindId(ROOT.into()), buf);
+        assert_eq!(
+            diagnostics[0].display(buf).to_string(),
+            "expected infoapi/string, but the expression resolves to test/player\n{greet(player)}\n       ^^^^^^"
+        );
+    }
+}
@@ -0,0 +1,743 @@
+//! Compares two versions of a schema to find changes that break existing templates,
+//! e.g. before releasing a plugin update.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use crate::{
+    data::{All, KindId, MappingDef, MappingName, QualifiedRef},
+    resolve,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// The kind or mapping that the change is about.
+    pub subject: String,
+    pub kind: ChangeKind,
+}
+
+impl Change {
+    pub fn is_breaking(&self) -> bool {
+        self.kind.is_breaking()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    KindAdded,
+    KindRemoved,
+    CanDisplayAdded,
+    CanDisplayRemoved,
+    MappingAdded,
+    MappingRemoved,
+    /// The mapping was removed, but an equivalent mapping was added under another name.
+    MappingRenamed {
+        new_name: MappingName,
+    },
+    TargetKind {
+        old: KindId,
+        new: KindId,
+    },
+    ImplicitAdded,
+    ImplicitRemoved,
+    MutableChanged {
+        new: bool,
+    },
+    ParamAdded {
+        name: String,
+        optional: bool,
+    },
+    ParamRemoved {
+        name: String,
+    },
+    ParamKind {
+        name: String,
+        old: KindId,
+        new: KindId,
+    },
+    ParamRequired {
+        name: String,
+    },
+    ParamOptional {
+        name: String,
+    },
+    ParamMultiAdded {
+        name: String,
+    },
+    ParamMultiRemoved {
+        name: String,
+    },
+    /// The shortest name of the mapping in the old schema no longer resolves to it in the new schema,
+    /// either because it is ambiguous or because another mapping matches it better.
+    NewlyAmbiguous {
+        short_name: String,
+        /// The kind that implicitly converts to the source kind of the mapping where the name is written,
+        /// or `None` if it is written on the source kind itself.
+        via: Option<KindId>,
+    },
+}
+
+impl ChangeKind {
+    /// Whether templates that worked with the old schema may fail to resolve or display with the new schema.
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            Self::KindAdded
+            | Self::CanDisplayAdded
+            | Self::MappingAdded
+            | Self::ImplicitAdded
+            | Self::MutableChanged { .. }
+            | Self::ParamOptional { .. }
+            | Self::ParamMultiAdded { .. } => false,
+            Self::ParamAdded { optional, .. } => !optional,
+            Self::KindRemoved
+            | Self::CanDisplayRemoved
+            | Self::MappingRemoved
+            | Self::MappingRenamed { .. }
+            | Self::TargetKind { .. }
+            | Self::ImplicitRemoved
+            | Self::ParamRemoved { .. }
+            | Self::ParamKind { .. }
+            | Self::ParamRequired { .. }
+            | Self::ParamMultiRemoved { .. }
+            | Self::NewlyAmbiguous { .. } => true,
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KindAdded => write!(f, "kind added"),
+            Self::KindRemoved => write!(f, "kind removed"),
+            Self::CanDisplayAdded => write!(f, "can now be displayed"),
+            Self::CanDisplayRemoved => write!(f, "can no longer be displayed"),
+            Self::MappingAdded => write!(f, "mapping added"),
+            Self::MappingRemoved => write!(f, "mapping removed"),
+            Self::MappingRenamed { new_name } => write!(
+                f,
+                "renamed to {}, keep the old name as an alias to avoid breaking templates",
+                new_name.0
+            ),
+            Self::TargetKind { old, new } => {
+                write!(f, "target kind changed from {} to {}", old.0, new.0)
+            }
+            Self::ImplicitAdded => write!(f, "now an implicit mapping"),
+            Self::ImplicitRemoved => write!(f, "no longer an implicit mapping"),
+            Self::MutableChanged { new: true } => write!(f, "now updates live"),
+            Self::MutableChanged { new: false } => write!(f, "no longer updates live"),
+            Self::ParamAdded {
+                name,
+                optional: true,
+            } => write!(f, "optional parameter {name} added"),
+            Self::ParamAdded {
+                name,
+                optional: false,
+            } => write!(f, "required parameter {name} added"),
+            Self::ParamRemoved { name } => write!(f, "parameter {name} removed"),
+            Self::ParamKind { name, old, new } => {
+                write!(f, "parameter {name} changed from {} to {}", old.0, new.0)
+            }
+            Self::ParamRequired { name } => write!(f, "parameter {name} is now required"),
+            Self::ParamOptional { name } => write!(f, "parameter {name} is now optional"),
+            Self::ParamMultiAdded { name } => {
+                write!(f, "parameter {name} now accepts multiple values")
+            }
+            Self::ParamMultiRemoved { name } => {
+                write!(f, "parameter {name} no longer accepts multiple values")
+            }
+            Self::NewlyAmbiguous {
+                short_name,
+                via: None,
+            } => write!(
+                f,
+                "`{short_name}` is now ambiguous and must be written with more qualifiers"
+            ),
+            Self::NewlyAmbiguous {
+                short_name,
+                via: Some(via),
+            } => write!(
+                f,
+                "`{short_name}` written after {} is now ambiguous and must be written with more qualifiers",
+                via.0
+            ),
+        }
+    }
+}
+
+/// Compares all kinds and mappings, ordered by kind and then by mapping name.
+pub fn diff(old: &All, new: &All) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    // mappings may be registered on kinds that are not defined
+    let kinds: BTreeSet<_> = [old, new]
+        .into_iter()
+        .flat_map(|schema| schema.kinds.keys().chain(schema.mappings.keys()))
+        .collect();
+
+    // templates may call a mapping after any kind that is implicitly converted to its source kind
+    let mut callers: BTreeMap<KindId, BTreeSet<KindId>> = BTreeMap::new();
+    for &kind in &kinds {
+        for reachable in resolve::implicit_closure(old, BTreeSet::from([kind.clone()])) {
+            callers.entry(reachable).or_default().insert(kind.clone());
+        }
+    }
+
+    let no_callers = BTreeSet::new();
+    for kind in kinds {
+        let callers = callers.get(kind).unwrap_or(&no_callers);
+        diff_kind(old, new, kind, callers, &mut changes);
+    }
+
+    changes
+}
+
+fn diff_kind(
+    old: &All,
+    new: &All,
+    kind: &KindId,
+    callers: &BTreeSet<KindId>,
+    changes: &mut Vec<Change>,
+) {
+    let mut push = |subject: &str, change| {
+        changes.push(Change {
+            subject: subject.to_string(),
+            kind: change,
+        })
+    };
+
+    match (old.kinds.get(kind), new.kinds.get(kind)) {
+        (Some(_), None) => push(&kind.0, ChangeKind::KindRemoved),
+        (None, Some(_)) => push(&kind.0, ChangeKind::KindAdded),
+        (Some(old_def), Some(new_def)) => match (old_def.can_display, new_def.can_display) {
+            (true, false) => push(&kind.0, ChangeKind::CanDisplayRemoved),
+            (false, true) => push(&kind.0, ChangeKind::CanDisplayAdded),
+            _ => {}
+        },
+        (None, None) => {}
+    }
+
+    let old_mappings = old.mappings.get(kind);
+    let new_mappings = new.mappings.get(kind);
+    let names: BTreeSet<_> = old_mappings
+        .into_iter()
+        .chain(new_mappings)
+        .flat_map(|mappings| mappings.keys())
+        .collect();
+
+    for name in names {
+        let subject = format!("{} on {}", name.0, kind.0);
+        let old_mapping = old_mappings.and_then(|mappings| mappings.get(name));
+        let new_mapping = new_mappings.and_then(|mappings| mappings.get(name));
+
+        match (old_mapping, new_mapping) {
+            (Some(old_mapping), None) => {
+                let renamed = new_mappings
+                    .into_iter()
+                    .flat_map(|mappings| mappings.values())
+                    .find(|candidate| {
+                        !old_mappings.is_some_and(|mappings| mappings.contains_key(&candidate.name))
+                            && is_equivalent(old_mapping, candidate)
+                    });
+                push(
+                    &subject,
+                    match renamed {
+                        Some(renamed) => ChangeKind::MappingRenamed {
+                            new_name: renamed.name.clone(),
+                        },
+                        None => ChangeKind::MappingRemoved,
+                    },
+                );
+            }
+            (None, Some(_)) => push(&subject, ChangeKind::MappingAdded),
+            (Some(old_mapping), Some(new_mapping)) => {
+                for change in diff_mapping(old, old_mapping, new, new_mapping, callers) {
+                    push(&subject, change);
+                }
+            }
+            (None, None) => unreachable!("name is collected from either schema"),
+        }
+    }
+}
+
+/// Whether `new` looks like `old` under another name,
+/// i.e. it has the same signature and either the same last token or the same help text.
+fn is_equivalent(old: &MappingDef, new: &MappingDef) -> bool {
+    old.target_kind == new.target_kind
+        && old.is_implicit == new.is_implicit
+        && old.parameters.len() == new.parameters.len()
+        && old
+            .parameters
+            .iter()
+            .zip(&new.parameters)
+            .all(|(old, new)| old.kind == new.kind && old.optional == new.optional)
+        && (old.name.last() == new.name.last() || (!old.help.is_empty() && old.help == new.help))
+}
+
+/// Compares two versions of a mapping.
+///
+/// `callers` are the kinds in the old schema that are implicitly converted to the source kind,
+/// including the source kind itself.
+fn diff_mapping(
+    old_schema: &All,
+    old: &MappingDef,
+    new_schema: &All,
+    new: &MappingDef,
+    callers: &BTreeSet<KindId>,
+) -> Vec<ChangeKind> {
+    let mut changes = Vec::new();
+
+    if old.target_kind != new.target_kind {
+        changes.push(ChangeKind::TargetKind {
+            old: old.target_kind.clone(),
+            new: new.target_kind.clone(),
+        });
+    }
+    match (old.is_implicit, new.is_implicit) {
+        (true, false) => changes.push(ChangeKind::ImplicitRemoved),
+        (false, true) => changes.push(ChangeKind::ImplicitAdded),
+        _ => {}
+    }
+    if old.mutable != new.mutable {
+        changes.push(ChangeKind::MutableChanged { new: new.mutable });
+    }
+
+    for old_param in &old.parameters {
+        let name = old_param.name.0.clone();
+        let Some(new_param) = new
+            .parameters
+            .iter()
+            .find(|param| param.name == old_param.name)
+        else {
+            changes.push(ChangeKind::ParamRemoved { name });
+            continue;
+        };
+
+        if old_param.kind != new_param.kind {
+            changes.push(ChangeKind::ParamKind {
+                name: name.clone(),
+                old: old_param.kind.clone(),
+                new: new_param.kind.clone(),
+            });
+        }
+        match (old_param.optional, new_param.optional) {
+            (true, false) => changes.push(ChangeKind::ParamRequired { name: name.clone() }),
+            (false, true) => changes.push(ChangeKind::ParamOptional { name: name.clone() }),
+            _ => {}
+        }
+        match (old_param.multi, new_param.multi) {
+            (true, false) => changes.push(ChangeKind::ParamMultiRemoved { name }),
+            (false, true) => changes.push(ChangeKind::ParamMultiAdded { name }),
+            _ => {}
+        }
+    }
+    for new_param in &new.parameters {
+        if !old
+            .parameters
+            .iter()
+            .any(|param| param.name == new_param.name)
+        {
+            changes.push(ChangeKind::ParamAdded {
+                name: new_param.name.0.clone(),
+                optional: new_param.optional,
+            });
+        }
+    }
+
+    // templates written with the old schema most likely use the shortest name
+    let short_name = old.name.minify(
+        old_schema
+            .mappings
+            .get(&old.source_kind)
+            .into_iter()
+            .flat_map(|mappings| mappings.keys()),
+    );
+    let short_ref = QualifiedRef::parse(&short_name);
+    for caller in callers {
+        if resolve::resolves_to(old_schema, caller, &short_ref, old)
+            && !resolve::resolves_to(new_schema, caller, &short_ref, new)
+        {
+            changes.push(ChangeKind::NewlyAmbiguous {
+                short_name: short_name.clone(),
+                via: (caller != &old.source_kind).then(|| caller.clone()),
+            });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::testing::{implicit, mapping, param, schema, with_params};
+
+    const ROOT: &str = "test/root";
+    const PLAYER: &str = "test/player";
+    const KINDS: &[(&str, bool)] = &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)];
+
+    fn with(mut mapping: Value, key: &str, value: impl Into<Value>) -> Value {
+        mapping[key] = value.into();
+        mapping
+    }
+
+    fn changes(old: &All, new: &All) -> Vec<(String, ChangeKind)> {
+        diff(old, new)
+            .into_iter()
+            .map(|change| (change.subject, change.kind))
+            .collect()
+    }
+
+    fn subject(name: &str) -> String {
+        format!("{name} on {ROOT}")
+    }
+
+    #[test]
+    fn diff_kinds() {
+        let old = schema(
+            &[("test/a", false), ("test/b", true), ("test/c", false)],
+            vec![],
+        );
+        let new = schema(
+            &[("test/a", true), ("test/b", false), ("test/d", false)],
+            vec![],
+        );
+
+        assert_eq!(
+            changes(&old, &new),
+            [
+                ("test/a".into(), ChangeKind::CanDisplayAdded),
+                ("test/b".into(), ChangeKind::CanDisplayRemoved),
+                ("test/c".into(), ChangeKind::KindRemoved),
+                ("test/d".into(), ChangeKind::KindAdded),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_mappings_and_renames() {
+        let old = schema(
+            KINDS,
+            vec![
+                mapping(ROOT, "a:name", KindId::STRING),
+                with(
+                    mapping(ROOT, "test:old", KindId::STRING),
+                    "help",
+                    "Shared help",
+                ),
+                mapping(ROOT, "test:gone", KindId::STRING),
+            ],
+        );
+        let new = schema(
+            KINDS,
+            vec![
+                mapping(ROOT, "b:name", KindId::STRING),
+                with(
+                    mapping(ROOT, "test:new", KindId::STRING),
+                    "help",
+                    "Shared help",
+                ),
+                mapping(ROOT, "test:other", PLAYER),
+            ],
+        );
+
+        assert_eq!(
+            changes(&old, &new),
+            [
+                (
+                    subject("a:name"),
+                    ChangeKind::MappingRenamed {
+                        new_name: MappingName("b:name".into())
+                    }
+                ),
+                (subject("b:name"), ChangeKind::MappingAdded),
+                (subject("test:gone"), ChangeKind::MappingRemoved),
+                (subject("test:new"), ChangeKind::MappingAdded),
+                (
+                    subject("test:old"),
+                    ChangeKind::MappingRenamed {
+                        new_name: MappingName("test:new".into())
+                    }
+                ),
+                (subject("test:other"), ChangeKind::MappingAdded),
+            ]
+        );
+    }
+
+    #[test]
+    fn equivalent_mappings() {
+        let parse = |value: Value| -> MappingDef { serde_json::from_value(value).unwrap() };
+        let old = parse(with_params(
+            mapping(ROOT, "a:name", KindId::STRING),
+            vec![param("x", KindId::STRING, true, false)],
+        ));
+        let equivalent = |value| is_equivalent(&old, &parse(value));
+
+        // the multi flag is not part of the signature
+        assert!(equivalent(with_params(
+            mapping(ROOT, "b:name", KindId::STRING),
+            vec![param("y", KindId::STRING, true, true)],
+        )));
+        assert!(!equivalent(with_params(
+            mapping(ROOT, "b:name", PLAYER),
+            vec![param("x", KindId::STRING, true, false)],
+        )));
+        assert!(!equivalent(with_params(
+            mapping(ROOT, "b:name", KindId::STRING),
+            vec![param("x", KindId::STRING, false, false)],
+        )));
+        assert!(!equivalent(with_params(
+            mapping(ROOT, "b:name", KindId::STRING),
+            vec![param("x", PLAYER, true, false)],
+        )));
+        assert!(!equivalent(mapping(ROOT, "b:name", KindId::STRING)));
+        assert!(!equivalent(with(
+            with_params(
+                mapping(ROOT, "test:nick", KindId::STRING),
+                vec![param("x", KindId::STRING, true, false)],
+            ),
+            "isImplicit",
+            true
+        )));
+
+        // empty help texts do not count as the same help
+        let old = parse(with(mapping(ROOT, "a:name", KindId::STRING), "help", ""));
+        assert!(!is_equivalent(
+            &old,
+            &parse(with(mapping(ROOT, "test:nick", KindId::STRING), "help", ""))
+        ));
+    }
+
+    #[test]
+    fn diff_mapping_signature() {
+        let old = schema(
+            KINDS,
+            vec![
+                with_params(
+                    mapping(ROOT, "test:a", KindId::STRING),
+                    vec![
+                        param("removed", KindId::STRING, false, false),
+                        param("kind", KindId::STRING, false, false),
+                        param("required", KindId::STRING, true, false),
+                        param("optional", KindId::STRING, false, false),
+                        param("multi", KindId::STRING, true, false),
+                        param("single", KindId::STRING, true, true),
+                    ],
+                ),
+                mapping(ROOT, "test:b", KindId::STRING),
+                implicit(ROOT, "test:c", PLAYER),
+            ],
+        );
+        let new = schema(
+            KINDS,
+            vec![
+                with_params(
+                    mapping(ROOT, "test:a", KindId::STRING),
+                    vec![
+                        param("kind", PLAYER, false, false),
+                        param("required", KindId::STRING, false, false),
+                        param("optional", KindId::STRING, true, false),
+                        param("multi", KindId::STRING, true, true),
+                        param("single", KindId::STRING, true, false),
+                        param("new-optional", KindId::STRING, true, false),
+                        param("new-required", KindId::STRING, false, false),
+                    ],
+                ),
+                with(
+                    with(implicit(ROOT, "test:b", PLAYER), "mutable", true),
+                    "parameters",
+                    Vec::<Value>::new(),
+                ),
+                mapping(ROOT, "test:c", PLAYER),
+            ],
+        );
+
+        let name = |name: &str| name.to_string();
+        assert_eq!(
+            changes(&old, &new),
+            [
+                (
+                    subject("test:a"),
+                    ChangeKind::ParamRemoved {
+                        name: name("removed")
+                    }
+                ),
+                (
+                    subject("test:a"),
+                    ChangeKind::ParamKind {
+                        name: name("kind"),
+                        old: KindId(KindId::STRING.into()),
+                        new: KindId(PLAYER.into()),
+                    }
+                ),
+                (
+                    subject("test:a"),
+                    ChangeKind::ParamRequired {
+                        name: name("required")
+                    }
+                ),
+                (
+                    subject("test:a"),
+                    ChangeKind::ParamOptional {
+                        name: name("optional")
+                    }
+                ),
+                (
+                    subject("test:a"),
+                    ChangeKind::ParamMultiAdded {
+                        name: name("multi")
+                    }
+                ),
+                (
+                    subject("test:a"),
+                    ChangeKind::ParamMultiRemoved {
+                        name: name("single")
+                    }
+                ),
+                (
+                    subject("test:a"),
+                    ChangeKind::ParamAdded {
+                        name: name("new-optional"),
+                        optional: true
+                    }
+                ),
+                (
+                    subject("test:a"),
+                    ChangeKind::ParamAdded {
+                        name: name("new-required"),
+                        optional: false
+                    }
+                ),
+                (
+                    subject("test:b"),
+                    ChangeKind::TargetKind {
+                        old: KindId(KindId::STRING.into()),
+                        new: KindId(PLAYER.into()),
+                    }
+                ),
+                (subject("test:b"), ChangeKind::ImplicitAdded),
+                (subject("test:b"), ChangeKind::MutableChanged { new: true }),
+                (subject("test:c"), ChangeKind::ImplicitRemoved),
+            ]
+        );
+    }
+
+    #[test]
+    fn breaking_changes() {
+        let name = String::new;
+        let kind = || KindId(KindId::STRING.into());
+        let compatible = [
+            ChangeKind::KindAdded,
+            ChangeKind::CanDisplayAdded,
+            ChangeKind::MappingAdded,
+            ChangeKind::ImplicitAdded,
+            ChangeKind::MutableChanged { new: true },
+            ChangeKind::MutableChanged { new: false },
+            ChangeKind::ParamAdded {
+                name: name(),
+                optional: true,
+            },
+            ChangeKind::ParamOptional { name: name() },
+            ChangeKind::ParamMultiAdded { name: name() },
+        ];
+        let breaking = [
+            ChangeKind::KindRemoved,
+            ChangeKind::CanDisplayRemoved,
+            ChangeKind::MappingRemoved,
+            ChangeKind::MappingRenamed {
+                new_name: MappingName(name()),
+            },
+            ChangeKind::TargetKind {
+                old: kind(),
+                new: kind(),
+            },
+            ChangeKind::ImplicitRemoved,
+            ChangeKind::ParamAdded {
+                name: name(),
+                optional: false,
+            },
+            ChangeKind::ParamRemoved { name: name() },
+            ChangeKind::ParamKind {
+                name: name(),
+                old: kind(),
+                new: kind(),
+            },
+            ChangeKind::ParamRequired { name: name() },
+            ChangeKind::ParamMultiRemoved { name: name() },
+            ChangeKind::NewlyAmbiguous {
+                short_name: name(),
+                via: None,
+            },
+        ];
+
+        for change in compatible {
+            assert!(!change.is_breaking(), "{change:?}");
+        }
+        for change in breaking {
+            assert!(change.is_breaking(), "{change:?}");
+        }
+    }
+
+    #[test]
+    fn newly_ambiguous_on_the_same_kind() {
+        let old = schema(KINDS, vec![mapping(ROOT, "test:name", KindId::STRING)]);
+        let new = schema(
+            KINDS,
+            vec![
+                mapping(ROOT, "test:name", KindId::STRING),
+                mapping(ROOT, "other:name", KindId::STRING),
+            ],
+        );
+
+        assert_eq!(
+            changes(&old, &new),
+            [
+                (subject("other:name"), ChangeKind::MappingAdded),
+                (
+                    subject("test:name"),
+                    ChangeKind::NewlyAmbiguous {
+                        short_name: "name".into(),
+                        via: None,
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn newly_ambiguous_through_implicit_mappings() {
+        let old = schema(
+            KINDS,
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                mapping(PLAYER, "test:name", KindId::STRING),
+            ],
+        );
+        // `name` after the root kind now resolves to the new mapping instead of `test:name` on players
+        let new = schema(
+            KINDS,
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                mapping(PLAYER, "test:name", KindId::STRING),
+                mapping(ROOT, "other:name", KindId::STRING),
+            ],
+        );
+
+        assert_eq!(
+            changes(&old, &new),
+            [
+                (
+                    format!("test:name on {PLAYER}"),
+                    ChangeKind::NewlyAmbiguous {
+                        short_name: "name".into(),
+                        via: Some(KindId(ROOT.into())),
+                    }
+                ),
+                (subject("other:name"), ChangeKind::MappingAdded),
+            ]
+        );
+    }
+}
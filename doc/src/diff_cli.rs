@@ -0,0 +1,108 @@
+//! The `infoapi-diff` command line tool, which compares two versions of exported schemas.
+//!
+//! Exits with 1 if any change may break existing templates, or 2 if the input cannot be read.
+
+use std::process::ExitCode;
+
+use crate::{
+    cli::{Arg, Args},
+    data, diff,
+};
+
+const USAGE: &str = "\
+Usage: infoapi-diff [OPTIONS] --old <FILE>... --new <FILE>...
+
+Lists the changes between two versions of schemas exported by `Doc::export`,
+and whether they may break templates written for the old version.
+
+Options:
+      --old <FILE>     A schema JSON file of the old version, may be repeated to merge multiple schemas
+      --new <FILE>     A schema JSON file of the new version, may be repeated to merge multiple schemas
+      --breaking-only  Only list the breaking changes
+  -h, --help           Print this message
+";
+
+pub fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    old: Vec<String>,
+    new: Vec<String>,
+    breaking_only: bool,
+}
+
+impl Options {
+    /// Parses the command line arguments, or returns `None` if help is requested.
+    fn parse(args: impl Iterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        let mut options = Self::default();
+        let mut args = Args::new(args);
+
+        while let Some(arg) = args.next()? {
+            let flag = match arg {
+                Arg::Positional(arg) => anyhow::bail!("unexpected argument {arg}"),
+                Arg::Flag(flag) => flag,
+            };
+
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--old" => options.old.push(args.value()?),
+                "--new" => options.new.push(args.value()?),
+                "--breaking-only" => options.breaking_only = true,
+                _ => anyhow::bail!("unknown option {flag}"),
+            }
+        }
+
+        anyhow::ensure!(!options.old.is_empty(), "no old schema files given");
+        anyhow::ensure!(!options.new.is_empty(), "no new schema files given");
+        Ok(Some(options))
+    }
+}
+
+/// Prints the changes, breaking changes first.
+///
+/// Returns whether there are no breaking changes.
+fn run(options: &Options) -> anyhow::Result<bool> {
+    let old = data::load_files(&options.old)?;
+    let new = data::load_files(&options.new)?;
+
+    let (breaking, other): (Vec<_>, Vec<_>) = diff::diff(&old, &new)
+        .into_iter()
+        .partition(diff::Change::is_breaking);
+
+    for change in &breaking {
+        println!("breaking: {}: {}", change.subject, change.kind);
+    }
+    if !options.breaking_only {
+        for change in &other {
+            println!("compatible: {}: {}", change.subject, change.kind);
+        }
+    }
+
+    eprintln!(
+        "{} breaking changes, {} compatible changes",
+        breaking.len(),
+        other.len()
+    );
+    Ok(breaking.is_empty())
+}
@@ -0,0 +1,690 @@
+//! Editable template document, mirroring the structure of `Ast\Template`.
+
+use std::{mem, sync::Arc};
+
+use crate::{
+    ast,
+    data::{All, KindId, MappingDef, QualifiedRef},
+    resolve::{self, UnmatchedArg},
+};
+
+/// The value passed to one parameter of a mapping.
+#[derive(Clone, PartialEq)]
+pub enum ArgValue {
+    /// A JSON literal, e.g. `"text"`, `1.5` or `true`.
+    Literal(String),
+    /// A coalescence chain of info expressions resolved from the template source kind.
+    Expr(Chain),
+}
+
+impl ArgValue {
+    pub fn default_for(kind: &KindId) -> Self {
+        match kind.0.as_str() {
+            KindId::STRING => Self::Literal("\"\"".into()),
+            KindId::INTEGER | KindId::FLOAT => Self::Literal("0".into()),
+            KindId::BOOL => Self::Literal("false".into()),
+            _ => Self::Expr(vec![Vec::new()]),
+        }
+    }
+
+    pub fn to_template_string(&self) -> String {
+        match self {
+            Self::Literal(json) => json.trim().to_string(),
+            Self::Expr(chain) => chain_string(chain),
+        }
+    }
+}
+
+/// Validates a literal the same way `Parse::parseValue` accepts JSON values.
+pub fn validate_literal(json: &str) -> Result<(), String> {
+    match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(
+            serde_json::Value::Bool(_)
+            | serde_json::Value::Number(_)
+            | serde_json::Value::String(_),
+        ) => Ok(()),
+        Ok(_) => Err("only strings, numbers and booleans can be passed as literals".into()),
+        Err(err) => Err(format!("invalid JSON: {err}")),
+    }
+}
+
+#[derive(Clone)]
+pub struct Step {
+    pub mapping: Arc<MappingDef>,
+    /// The name written in the template.
+    ///
+    /// This is the shortest unambiguous name for mappings chosen in the builder,
+    /// but parsed templates keep the name as written, e.g. after choosing to use the fully qualified name.
+    pub minified_name: String,
+    /// The argument value for each parameter of the mapping,
+    /// or `None` for a skipped optional parameter.
+    ///
+    /// The server only uses one value even for `multi` parameters.
+    pub args: Vec<Option<ArgValue>>,
+    /// Whether this is an implicit mapping inserted by the server,
+    /// which is not written in the template.
+    pub inserted: bool,
+}
+
+impl PartialEq for Step {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.mapping, &other.mapping)
+            && self.minified_name == other.minified_name
+            && self.args == other.args
+            && self.inserted == other.inserted
+    }
+}
+
+impl Step {
+    /// Creates a step for `mapping` called directly on its source kind.
+    pub fn new(schema: &All, mapping: Arc<MappingDef>) -> Self {
+        let kind = mapping.source_kind.clone();
+        Self::after_implicit(schema, mapping, &kind)
+    }
+
+    /// Creates a step for `mapping` called after a value of `kind`,
+    /// which the server converts to the source kind of the mapping through implicit mappings.
+    pub fn after_implicit(schema: &All, mapping: Arc<MappingDef>, kind: &KindId) -> Self {
+        let minified_name = resolve::shortest_name(schema, kind, &mapping);
+        Self::with_minified_name(mapping, minified_name)
+    }
+
+    fn with_minified_name(mapping: Arc<MappingDef>, minified_name: String) -> Self {
+        let args = mapping
+            .parameters
+            .iter()
+            .map(|param| (!param.optional).then(|| ArgValue::default_for(&param.kind)))
+            .collect();
+        Self {
+            mapping,
+            minified_name,
+            args,
+            inserted: false,
+        }
+    }
+
+    /// Creates a step for an implicit mapping that the server inserts automatically.
+    pub fn inserted(schema: &All, mapping: Arc<MappingDef>) -> Self {
+        Self {
+            inserted: true,
+            ..Self::new(schema, mapping)
+        }
+    }
+
+    /// Whether the name, when called after a value of `kind`,
+    /// resolves to another mapping or is ambiguous on the server.
+    pub fn is_ambiguous(&self, schema: &All, kind: &KindId) -> bool {
+        !self.inserted
+            && !resolve::resolves_to(
+                schema,
+                kind,
+                &QualifiedRef::parse(&self.minified_name),
+                &self.mapping,
+            )
+    }
+
+    /// Switches to another name of the same mapping according to `preference`.
+    ///
+    /// All names of a mapping share the same parameters, so the arguments are kept.
+    fn with_name_preference(&self, schema: &All, preference: NamePreference) -> Self {
+        let renamed = match preference {
+            _ if self.inserted => None,
+            NamePreference::AsChosen => None,
+            NamePreference::Canonical => schema.canonical_of(&self.mapping).cloned(),
+            NamePreference::Alias => schema
+                .aliases_of(&self.mapping)
+                .map(|alias| Step::new(schema, alias.clone()))
+                .min_by_key(|alias| alias.minified_name.len())
+                .map(|alias| alias.mapping),
+        };
+
+        let mut step = match renamed {
+            Some(mapping) => Self {
+                args: self.args.clone(),
+                ..Self::new(schema, mapping)
+            },
+            None => self.clone(),
+        };
+        for value in step.args.iter_mut().flatten() {
+            if let ArgValue::Expr(chain) = value {
+                *chain = chain_with_name_preference(schema, chain, preference);
+            }
+        }
+        step
+    }
+
+    /// Formats this step as a mapping call accepted by `Parse::parseCall`.
+    ///
+    /// Arguments are passed positionally as long as `Template::matchArgsToParams`
+    /// assigns them to the same parameters, and by name otherwise.
+    pub fn call_string(&self) -> String {
+        let params = &self.mapping.parameters;
+        let mut arg_names = Vec::new();
+        let mut arg_strings = Vec::new();
+
+        for (index, (param, value)) in params.iter().zip(&self.args).enumerate() {
+            let Some(value) = value else { continue };
+            let value = value.to_template_string();
+
+            arg_names.push(None);
+            let matched = resolve::match_args_to_params(params, arg_names.iter().copied());
+            if matched.params[index] == Some(arg_names.len() - 1) {
+                arg_strings.push(value);
+            } else {
+                *arg_names.last_mut().expect("just pushed") = Some(param.name.0.as_str());
+                arg_strings.push(format!("{}={value}", param.name.0));
+            }
+        }
+
+        if arg_strings.is_empty() {
+            self.minified_name.clone()
+        } else {
+            format!("{}({})", self.minified_name, arg_strings.join(", "))
+        }
+    }
+}
+
+/// A coalescence chain of alternative paths, separated by `|` in the template.
+pub type Chain = Vec<Vec<Step>>;
+
+/// Which name to write for mappings registered under multiple names.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NamePreference {
+    /// Keep the name selected in the builder.
+    #[default]
+    AsChosen,
+    /// Write the first name registered for the mapping.
+    Canonical,
+    /// Write the alias with the shortest minified name.
+    Alias,
+}
+
+fn chain_with_name_preference(schema: &All, chain: &Chain, preference: NamePreference) -> Chain {
+    chain
+        .iter()
+        .map(|path| {
+            path.iter()
+                .map(|step| step.with_name_preference(schema, preference))
+                .collect()
+        })
+        .collect()
+}
+
+/// The entire template being edited.
+#[derive(Clone, PartialEq)]
+pub struct Document {
+    pub elements: Vec<Element>,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self {
+            elements: vec![Element::Expr(vec![Vec::new()])],
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Element {
+    /// Raw text in its unescaped form.
+    RawText(String),
+    /// A placeholder expression.
+    Expr(Chain),
+}
+
+impl Document {
+    /// Parses a template and resolves all expressions from `source_kind`.
+    ///
+    /// Adjacent raw text elements, including escaped braces, are merged into one.
+    pub fn parse(schema: &All, source_kind: &KindId, input: &str) -> Result<Self, String> {
+        let template = ast::parse(input).map_err(|err| err.to_string())?;
+
+        let mut elements = Vec::new();
+        for element in &template.elements {
+            match element {
+                ast::Element::RawText(text) => {
+                    if let Some(Element::RawText(last)) = elements.last_mut() {
+                        last.push_str(&text.parsed);
+                    } else {
+                        elements.push(Element::RawText(text.parsed.clone()));
+                    }
+                }
+                ast::Element::Expr(expr) => {
+                    let chain = chain_from_expr(schema, source_kind, expr, None)?;
+                    elements.push(Element::Expr(chain));
+                }
+            }
+        }
+
+        Ok(Self { elements })
+    }
+
+    /// Splits the raw text at `element` on the byte `offset` and inserts an empty placeholder there.
+    ///
+    /// Returns the index of the new placeholder after normalization.
+    pub fn insert_expr(&mut self, element: usize, offset: usize) -> usize {
+        let mut tail = self.elements.split_off(element.min(self.elements.len()));
+        if let Some(Element::RawText(text)) = tail.first_mut() {
+            let suffix = text.split_off(offset);
+            let prefix = mem::replace(text, suffix);
+            self.elements.push(Element::RawText(prefix));
+        }
+
+        // placeholders are never merged, so normalizing the rest keeps the index valid
+        self.normalize();
+        let index = self.elements.len();
+        self.elements.push(Element::Expr(vec![Vec::new()]));
+        self.elements.extend(tail);
+        self.normalize();
+        index
+    }
+
+    /// Returns the index of the raw text at the end of the document,
+    /// appending an empty one if the document ends with a placeholder.
+    pub fn push_text(&mut self) -> usize {
+        self.normalize();
+        if !matches!(self.elements.last(), Some(Element::RawText(_))) {
+            // kept until the next normalization so that the user can type into it
+            self.elements.push(Element::RawText(String::new()));
+        }
+        self.elements.len() - 1
+    }
+
+    /// Swaps the element at `index` with the one before it.
+    pub fn move_up(&mut self, index: usize) {
+        if index > 0 && index < self.elements.len() {
+            self.elements.swap(index - 1, index);
+            self.normalize();
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.elements.len() {
+            self.elements.remove(index);
+            self.normalize();
+        }
+    }
+
+    /// Merges adjacent raw text and removes empty raw text.
+    fn normalize(&mut self) {
+        let mut elements: Vec<Element> = Vec::with_capacity(self.elements.len());
+        for element in self.elements.drain(..) {
+            match (elements.last_mut(), element) {
+                (_, Element::RawText(text)) if text.is_empty() => {}
+                (Some(Element::RawText(last)), Element::RawText(text)) => last.push_str(&text),
+                (_, element) => elements.push(element),
+            }
+        }
+        self.elements = elements;
+    }
+
+    /// Rewrites every mapping call to use the name selected by `preference`.
+    pub fn with_name_preference(&self, schema: &All, preference: NamePreference) -> Self {
+        let elements = self
+            .elements
+            .iter()
+            .map(|element| match element {
+                Element::RawText(text) => Element::RawText(text.clone()),
+                Element::Expr(chain) => {
+                    Element::Expr(chain_with_name_preference(schema, chain, preference))
+                }
+            })
+            .collect();
+        Self { elements }
+    }
+
+    /// Formats the document as a template string, escaping braces in raw text.
+    pub fn template_string(&self) -> String {
+        let mut out = String::new();
+        for element in &self.elements {
+            match element {
+                Element::RawText(text) => {
+                    out.push_str(&text.replace('{', "{{").replace('}', "}}"));
+                }
+                Element::Expr(chain) => {
+                    out.push('{');
+                    out.push_str(&chain_string(chain));
+                    out.push('}');
+                }
+            }
+        }
+        out
+    }
+}
+
+/// The kind after which each step of a path starting from `source_kind` is written,
+/// i.e. before the implicit mappings inserted for the step.
+pub fn written_after<'t>(source_kind: &'t KindId, path: &'t [Step]) -> Vec<&'t KindId> {
+    let mut kind = source_kind;
+    path.iter()
+        .map(|step| {
+            let written_after = kind;
+            if !step.inserted {
+                kind = &step.mapping.target_kind;
+            }
+            written_after
+        })
+        .collect()
+}
+
+/// Formats a path as the space-separated calls inside `{}`.
+pub fn path_string(path: &[Step]) -> String {
+    let step_strings: Vec<_> = path
+        .iter()
+        .filter(|step| !step.inserted)
+        .map(Step::call_string)
+        .collect();
+    step_strings.join(" ")
+}
+
+/// Formats a coalescence chain as the contents inside `{}`.
+pub fn chain_string(chain: &Chain) -> String {
+    let path_strings: Vec<_> = chain.iter().map(|path| path_string(path)).collect();
+    path_strings.join(" | ")
+}
+
+/// Resolves each alternative of a coalescence chain from `root_kind`.
+///
+/// Each alternative must resolve to `expect_kind` if specified, or a displayable kind otherwise.
+fn chain_from_expr(
+    schema: &All,
+    root_kind: &KindId,
+    expr: &ast::Expr,
+    expect_kind: Option<&KindId>,
+) -> Result<Chain, String> {
+    let mut chain = Vec::new();
+    let mut next = Some(expr);
+    while let Some(expr) = next {
+        chain.push(steps_from_info_expr(
+            schema,
+            root_kind,
+            &expr.main,
+            expect_kind,
+        )?);
+        next = expr.else_.as_deref();
+    }
+    Ok(chain)
+}
+
+fn steps_from_info_expr(
+    schema: &All,
+    root_kind: &KindId,
+    expr: &ast::InfoExpr,
+    expect_kind: Option<&KindId>,
+) -> Result<Vec<Step>, String> {
+    let hops = resolve::resolve_info_expr(schema, root_kind, expr, |kind| {
+        schema.admits(kind, expect_kind)
+    })
+    .map_err(|err| err.to_string())?;
+
+    hops.into_iter()
+        .map(|hop| {
+            let Some(call) = hop.call else {
+                return Ok(Step::inserted(schema, hop.mapping));
+            };
+
+            let mut step = Step::new(schema, hop.mapping);
+            step.minified_name = expr.calls[call].name.to_string();
+            if let Some(args) = &expr.calls[call].args {
+                step.args = args_from_ast(schema, root_kind, &step.mapping, args)?;
+            }
+            Ok(step)
+        })
+        .collect()
+}
+
+/// Assigns parsed arguments to parameters with `resolve::match_args_to_params`.
+///
+/// Unlike the server, unknown arguments are reported as errors instead of being dropped.
+/// Arguments overwritten by later ones are dropped like on the server.
+fn args_from_ast(
+    schema: &All,
+    root_kind: &KindId,
+    mapping: &MappingDef,
+    ast_args: &[ast::Arg],
+) -> Result<Vec<Option<ArgValue>>, String> {
+    let params = &mapping.parameters;
+    let matched =
+        resolve::match_args_to_params(params, ast_args.iter().map(|arg| arg.name.as_deref()));
+
+    for (ast_arg, index) in ast_args.iter().zip(&matched.args) {
+        match index {
+            Ok(_) => {}
+            Err(UnmatchedArg::UnknownName) => {
+                let name = ast_arg.name.as_deref().unwrap_or_default();
+                return Err(format!(
+                    "`{}` has no parameter called `{name}`",
+                    mapping.name.0
+                ));
+            }
+            Err(UnmatchedArg::TooMany) => {
+                return Err(format!("too many arguments for `{}`", mapping.name.0));
+            }
+        }
+    }
+
+    params
+        .iter()
+        .zip(&matched.params)
+        .map(|(param, arg)| {
+            let Some(arg) = arg else {
+                return Ok((!param.optional).then(|| ArgValue::default_for(&param.kind)));
+            };
+            let value = match &ast_args[*arg].value {
+                ast::ArgValue::Json(value) => ArgValue::Literal(value.json.clone()),
+                ast::ArgValue::Expr(expr) => {
+                    ArgValue::Expr(chain_from_expr(schema, root_kind, expr, Some(&param.kind))?)
+                }
+            };
+            Ok(Some(value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{implicit, mapping, param, schema, with_params};
+
+    const ROOT: &str = "test/root";
+
+    fn text(text: &str) -> Element {
+        Element::RawText(text.to_string())
+    }
+
+    fn is_text(element: &Element, expect: &str) -> bool {
+        matches!(element, Element::RawText(text) if text == expect)
+    }
+
+    #[test]
+    fn insert_expr_splits_text() {
+        let mut document = Document {
+            elements: vec![text("ab")],
+        };
+        assert_eq!(document.insert_expr(0, 1), 1);
+        assert_eq!(document.elements.len(), 3);
+        assert!(is_text(&document.elements[0], "a"));
+        assert!(matches!(document.elements[1], Element::Expr(_)));
+        assert!(is_text(&document.elements[2], "b"));
+    }
+
+    #[test]
+    fn insert_expr_at_text_start() {
+        let mut document = Document {
+            elements: vec![text("ab")],
+        };
+        assert_eq!(document.insert_expr(0, 0), 0);
+        assert!(matches!(document.elements[0], Element::Expr(_)));
+        assert!(is_text(&document.elements[1], "ab"));
+    }
+
+    #[test]
+    fn insert_expr_after_empty_text() {
+        let mut document = Document::default();
+        assert_eq!(document.push_text(), 1);
+        let end = document.elements.len();
+        assert_eq!(document.insert_expr(end, 0), 1);
+        assert_eq!(document.elements.len(), 2);
+        assert!(matches!(document.elements[1], Element::Expr(_)));
+    }
+
+    #[test]
+    fn push_text_reuses_trailing_text() {
+        let mut document = Document {
+            elements: vec![Element::Expr(Vec::new()), text("a"), text("b")],
+        };
+        assert_eq!(document.push_text(), 1);
+        assert_eq!(document.elements.len(), 2);
+        assert!(is_text(&document.elements[1], "ab"));
+    }
+
+    fn join_schema() -> All {
+        schema(
+            &[(ROOT, false), (KindId::STRING, true)],
+            vec![with_params(
+                mapping(ROOT, "test:join", KindId::STRING),
+                vec![
+                    param("first", KindId::STRING, false, false),
+                    param("second", KindId::STRING, true, false),
+                    param("rest", KindId::STRING, true, true),
+                ],
+            )],
+        )
+    }
+
+    fn parse_step(schema: &All, input: &str) -> Step {
+        let document = Document::parse(schema, &KindId(ROOT.into()), input).unwrap();
+        let [Element::Expr(chain)] = &document.elements[..] else {
+            panic!("expected one expression");
+        };
+        chain[0][0].clone()
+    }
+
+    #[test]
+    fn call_string_names_args_after_skipped_params() {
+        let schema = join_schema();
+        let mut step = parse_step(&schema, r#"{join("a")}"#);
+        assert_eq!(step.call_string(), r#"join("a")"#);
+
+        step.args[2] = Some(ArgValue::Literal("\"c\"".into()));
+        assert_eq!(step.call_string(), r#"join("a", rest="c")"#);
+
+        step.args[1] = Some(ArgValue::Literal("\"b\"".into()));
+        assert_eq!(step.call_string(), r#"join("a", "b", "c")"#);
+    }
+
+    #[test]
+    fn parse_keeps_the_value_used_by_the_server() {
+        let schema = join_schema();
+        let step = parse_step(&schema, r#"{join(rest="x", second="b", "a", rest="c")}"#);
+        assert_eq!(step.call_string(), r#"join("a", "b", "c")"#);
+    }
+
+    #[test]
+    fn parse_rejects_unmatched_args() {
+        let schema = join_schema();
+        let root = KindId(ROOT.into());
+        for input in [r#"{join("a", "b", "c", "d")}"#, r#"{join(other="a")}"#] {
+            assert!(Document::parse(&schema, &root, input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn name_steps_by_resolving_from_the_written_kind() {
+        const PLAYER: &str = "test/player";
+        let schema = schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                mapping(ROOT, "test:name", KindId::STRING),
+                mapping(PLAYER, "test:nick", KindId::STRING),
+                mapping(ROOT, "a:b:id", KindId::STRING),
+                mapping(PLAYER, "c:b:id", KindId::STRING),
+                mapping(PLAYER, "b:id", KindId::STRING),
+            ],
+        );
+        let root = KindId(ROOT.into());
+        let step = |kind: &str, name: &str| {
+            let mapping = schema.mappings[&KindId(kind.into())]
+                .values()
+                .find(|mapping| mapping.name.0 == name)
+                .unwrap();
+            Step::after_implicit(&schema, mapping.clone(), &root)
+        };
+
+        assert_eq!(step(ROOT, "test:name").minified_name, "name");
+        assert_eq!(step(PLAYER, "test:nick").minified_name, "nick");
+        // `b:id` alone would resolve to `a:b:id` on the root kind
+        assert_eq!(step(PLAYER, "c:b:id").minified_name, "c:id");
+
+        // `a:b:id` on the root kind shadows every name of `b:id` on players
+        let shadowed = step(PLAYER, "b:id");
+        assert_eq!(shadowed.minified_name, "b:id");
+        assert!(shadowed.is_ambiguous(&schema, &root));
+        assert!(!shadowed.is_ambiguous(&schema, &KindId(PLAYER.into())));
+        assert!(!step(PLAYER, "c:b:id").is_ambiguous(&schema, &root));
+    }
+
+    #[test]
+    fn qualify_names_shadowed_on_the_source_kind() {
+        const PLAYER: &str = "test/player";
+        let schema = schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                mapping(ROOT, "test:name", KindId::STRING),
+                mapping(PLAYER, "other:name", KindId::STRING),
+            ],
+        );
+        let root = KindId(ROOT.into());
+        let mapping = schema.mappings[&KindId(PLAYER.into())]
+            .values()
+            .next()
+            .unwrap();
+
+        // `name` is unique among the mappings on players,
+        // but the server resolves it to `test:name` on the root kind with fewer mappings
+        let step = Step::after_implicit(&schema, mapping.clone(), &root);
+        assert_eq!(step.minified_name, "other:name");
+        assert!(!step.is_ambiguous(&schema, &root));
+
+        let unqualified = Step {
+            minified_name: "name".into(),
+            ..step
+        };
+        assert!(unqualified.is_ambiguous(&schema, &root));
+        assert_eq!(Step::new(&schema, mapping.clone()).minified_name, "name");
+    }
+
+    #[test]
+    fn written_after_skips_inserted_steps() {
+        const PLAYER: &str = "test/player";
+        let schema = schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                mapping(PLAYER, "test:name", KindId::STRING),
+            ],
+        );
+        let root = KindId(ROOT.into());
+        let document = Document::parse(&schema, &root, "{name}").unwrap();
+        let [Element::Expr(chain)] = &document.elements[..] else {
+            panic!("expected one expression");
+        };
+
+        let kinds: Vec<_> = written_after(&root, &chain[0])
+            .into_iter()
+            .map(|kind| kind.0.as_str())
+            .collect();
+        assert_eq!(kinds, [ROOT, ROOT]);
+    }
+
+    #[test]
+    fn parse_keeps_names_as_written() {
+        let schema = join_schema();
+        for input in [r#"{join("a")}"#, r#"{test:join("a")}"#] {
+            let step = parse_step(&schema, input);
+            assert_eq!(format!("{{{}}}", step.call_string()), input);
+        }
+    }
+}
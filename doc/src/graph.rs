@@ -0,0 +1,415 @@
+//! Layered layout of the schema as a graph with kinds as nodes and mappings as edges.
+//!
+//! Kinds are assigned to columns by their distance from template kinds,
+//! then reordered within each column to reduce edge crossings.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    sync::Arc,
+};
+
+use crate::{
+    data::{All, KindId, MappingDef},
+    PluginFilter,
+};
+
+pub const NODE_HEIGHT: f64 = 28.0;
+const CHAR_WIDTH: f64 = 7.5;
+const NODE_PADDING: f64 = 16.0;
+const NODE_GAP: f64 = 20.0;
+const LAYER_GAP: f64 = 120.0;
+pub const MARGIN: f64 = 40.0;
+/// Number of barycenter sweeps used to reorder nodes within layers.
+const ORDER_SWEEPS: usize = 4;
+
+pub struct Layout {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub width: f64,
+    pub height: f64,
+    /// The index of each kind in `nodes`.
+    index: BTreeMap<KindId, usize>,
+}
+
+pub struct Node {
+    pub kind: KindId,
+    pub layer: usize,
+    /// The center of the node.
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+}
+
+/// All mappings from one kind to another, drawn as a single edge.
+pub struct Edge {
+    pub from: KindId,
+    pub to: KindId,
+    /// Implicit and explicit mappings between the same kinds are drawn as separate edges.
+    pub is_implicit: bool,
+    pub mappings: Vec<Arc<MappingDef>>,
+    /// SVG path data of the edge.
+    pub path: String,
+}
+
+impl Layout {
+    pub fn node(&self, kind: &KindId) -> Option<&Node> {
+        self.index.get(kind).map(|&index| &self.nodes[index])
+    }
+}
+
+/// Lays out the kinds and mappings from the plugins shown in `plugins`.
+///
+/// Aliases are omitted since they duplicate the edge of their canonical mapping.
+pub fn layout(schema: &All, plugins: &PluginFilter) -> Layout {
+    let mut grouped = BTreeMap::<(KindId, KindId, bool), Vec<Arc<MappingDef>>>::new();
+    for mapping in schema
+        .mappings
+        .values()
+        .flat_map(|mappings| mappings.values())
+        .filter(|mapping| {
+            mapping.metadata.alias_of.is_none()
+                && plugins.contains(mapping.metadata.source_plugin.as_ref())
+        })
+    {
+        let key = (
+            mapping.source_kind.clone(),
+            mapping.target_kind.clone(),
+            mapping.is_implicit,
+        );
+        grouped.entry(key).or_default().push(mapping.clone());
+    }
+
+    // kinds referenced by shown mappings are included even if they are undefined
+    let mut kinds: BTreeSet<KindId> = schema
+        .kinds
+        .iter()
+        .filter(|(_, def)| plugins.contains(def.metadata.source_plugin.as_ref()))
+        .map(|(kind, _)| kind.clone())
+        .collect();
+    for (from, to, _) in grouped.keys() {
+        kinds.insert(from.clone());
+        kinds.insert(to.clone());
+    }
+
+    let mut successors = BTreeMap::<KindId, BTreeSet<KindId>>::new();
+    let mut neighbors = BTreeMap::<KindId, BTreeSet<KindId>>::new();
+    for (from, to, _) in grouped.keys() {
+        if from != to {
+            successors
+                .entry(from.clone())
+                .or_default()
+                .insert(to.clone());
+            neighbors
+                .entry(from.clone())
+                .or_default()
+                .insert(to.clone());
+            neighbors
+                .entry(to.clone())
+                .or_default()
+                .insert(from.clone());
+        }
+    }
+
+    let layers = assign_layers(schema, &kinds, &successors);
+    let columns = order_layers(&layers, &neighbors);
+
+    let mut nodes = Vec::new();
+    let mut x = MARGIN;
+    let max_column_height = columns
+        .iter()
+        .map(|column| column_height(column.len()))
+        .fold(0.0, f64::max);
+    for (layer, column) in columns.iter().enumerate() {
+        let widths: Vec<_> = column.iter().map(|kind| node_width(kind)).collect();
+        let column_width = widths.iter().copied().fold(0.0, f64::max);
+        // columns are centered vertically
+        let top = MARGIN + (max_column_height - column_height(column.len())) / 2.0;
+        for (index, (kind, width)) in column.iter().zip(widths).enumerate() {
+            nodes.push(Node {
+                kind: (*kind).clone(),
+                layer,
+                x: x + column_width / 2.0,
+                y: top + index as f64 * (NODE_HEIGHT + NODE_GAP) + NODE_HEIGHT / 2.0,
+                width,
+            });
+        }
+        x += column_width + LAYER_GAP;
+    }
+
+    let index = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.kind.clone(), index))
+        .collect();
+    let mut layout = Layout {
+        nodes,
+        index,
+        edges: Vec::new(),
+        width: x - LAYER_GAP + MARGIN,
+        height: max_column_height + MARGIN * 2.0,
+    };
+
+    let edges = grouped
+        .into_iter()
+        .map(|((from, to, is_implicit), mappings)| Edge {
+            path: edge_path(&layout, &from, &to, is_implicit),
+            from,
+            to,
+            is_implicit,
+            mappings,
+        })
+        .collect();
+    layout.edges = edges;
+    layout
+}
+
+/// Assigns each kind the length of the shortest path from a template kind.
+///
+/// Kinds unreachable from template kinds start new layers from kinds without incoming mappings.
+fn assign_layers(
+    schema: &All,
+    kinds: &BTreeSet<KindId>,
+    successors: &BTreeMap<KindId, BTreeSet<KindId>>,
+) -> BTreeMap<KindId, usize> {
+    let mut layers = BTreeMap::new();
+    let mut queue = VecDeque::new();
+
+    let has_incoming: BTreeSet<&KindId> = successors.values().flatten().collect();
+    let roots = kinds.iter().filter(|kind| {
+        schema
+            .kinds
+            .get(kind)
+            .is_some_and(|def| def.metadata.is_root)
+    });
+    let sources = kinds.iter().filter(|kind| !has_incoming.contains(kind));
+
+    for start in roots.chain(sources).chain(kinds) {
+        if layers.contains_key(start) {
+            continue;
+        }
+        layers.insert(start.clone(), 0);
+        queue.push_back(start);
+
+        while let Some(kind) = queue.pop_front() {
+            let layer = layers[kind];
+            for next in successors.get(kind).into_iter().flatten() {
+                if !layers.contains_key(next) {
+                    layers.insert(next.clone(), layer + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    layers
+}
+
+/// Orders the kinds in each layer by the average position of their neighbors in adjacent layers.
+fn order_layers<'t>(
+    layers: &'t BTreeMap<KindId, usize>,
+    neighbors: &BTreeMap<KindId, BTreeSet<KindId>>,
+) -> Vec<Vec<&'t KindId>> {
+    let layer_count = layers.values().max().map_or(0, |&max| max + 1);
+    let mut columns = vec![Vec::new(); layer_count];
+    for (kind, &layer) in layers {
+        columns[layer].push(kind);
+    }
+
+    for sweep in 0..ORDER_SWEEPS {
+        // alternate between aligning with the previous and the next layer
+        let forward = sweep % 2 == 0;
+        let order: Vec<usize> = match forward {
+            true => (1..layer_count).collect(),
+            false => (0..layer_count.saturating_sub(1)).rev().collect(),
+        };
+
+        for layer in order {
+            let adjacent = if forward { layer - 1 } else { layer + 1 };
+            let positions: BTreeMap<&KindId, usize> = columns[adjacent]
+                .iter()
+                .enumerate()
+                .map(|(index, &kind)| (kind, index))
+                .collect();
+
+            let mut keyed: Vec<_> = columns[layer]
+                .iter()
+                .enumerate()
+                .map(|(index, &kind)| {
+                    let adjacent_positions: Vec<_> = neighbors
+                        .get(kind)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|neighbor| positions.get(neighbor))
+                        .collect();
+                    // nodes without neighbors in the adjacent layer keep their position
+                    let key = match adjacent_positions.len() {
+                        0 => index as f64,
+                        len => adjacent_positions.into_iter().sum::<usize>() as f64 / len as f64,
+                    };
+                    (key, kind)
+                })
+                .collect();
+            keyed.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+            columns[layer] = keyed.into_iter().map(|(_, kind)| kind).collect();
+        }
+    }
+
+    columns
+}
+
+fn node_width(kind: &KindId) -> f64 {
+    kind.0.chars().count() as f64 * CHAR_WIDTH + NODE_PADDING * 2.0
+}
+
+fn column_height(nodes: usize) -> f64 {
+    match nodes {
+        0 => 0.0,
+        nodes => nodes as f64 * (NODE_HEIGHT + NODE_GAP) - NODE_GAP,
+    }
+}
+
+/// Draws a curve from the right side of the source node to the left side of the target node.
+///
+/// Implicit edges are offset slightly so that they do not overlap explicit edges between the same kinds.
+fn edge_path(layout: &Layout, from: &KindId, to: &KindId, is_implicit: bool) -> String {
+    let (Some(source), Some(target)) = (layout.node(from), layout.node(to)) else {
+        return String::new();
+    };
+    let offset = if is_implicit { 4.0 } else { 0.0 };
+
+    if from == to {
+        // a loop above the node
+        let x = source.x + source.width / 4.0;
+        let y = source.y - NODE_HEIGHT / 2.0;
+        return format!(
+            "M {x0} {y} C {x0} {top}, {x1} {top}, {x1} {y}",
+            x0 = x - 10.0,
+            x1 = x + 10.0,
+            top = y - 30.0 - offset,
+        );
+    }
+
+    let x1 = source.x + source.width / 2.0;
+    let y1 = source.y + offset;
+    let x2 = target.x - target.width / 2.0;
+    let y2 = target.y + offset;
+    // backward and same-layer edges bend further out to stay visible
+    let bend = match target.layer > source.layer {
+        true => (x2 - x1) / 2.0,
+        false => LAYER_GAP,
+    };
+    format!(
+        "M {x1} {y1} C {} {y1}, {} {y2}, {x2} {y2}",
+        x1 + bend,
+        x2 - bend
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{implicit, mapping, schema};
+
+    const ROOT: &str = "test/root";
+    const PLAYER: &str = "test/player";
+
+    fn kind(id: &str) -> KindId {
+        KindId(id.into())
+    }
+
+    /// A template kind with a direct and an indirect route to strings.
+    fn fixture(mappings: Vec<serde_json::Value>) -> All {
+        let mut schema = schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            mappings,
+        );
+        let root = schema.kinds.get_mut(&kind(ROOT)).unwrap();
+        root.metadata.is_root = true;
+        schema
+    }
+
+    fn layers(layout: &Layout) -> Vec<(&str, usize)> {
+        let mut layers: Vec<_> = layout
+            .nodes
+            .iter()
+            .map(|node| (node.kind.0.as_str(), node.layer))
+            .collect();
+        layers.sort();
+        layers
+    }
+
+    #[test]
+    fn layer_by_distance_from_template_kinds() {
+        let schema = fixture(vec![
+            mapping(PLAYER, "test:name", KindId::STRING),
+            mapping(ROOT, "test:player", PLAYER),
+            mapping(ROOT, "test:server-name", KindId::STRING),
+            mapping(KindId::STRING, "test:root", ROOT),
+        ]);
+        let layout = layout(&schema, &PluginFilter::from_iter([]));
+
+        assert_eq!(
+            layers(&layout),
+            [(KindId::STRING, 1), (PLAYER, 1), (ROOT, 0)]
+        );
+        for node in &layout.nodes {
+            assert!(node.x - node.width / 2.0 >= MARGIN);
+            assert!(node.x + node.width / 2.0 <= layout.width - MARGIN);
+            assert!(node.y - NODE_HEIGHT / 2.0 >= MARGIN);
+            assert!(node.y + NODE_HEIGHT / 2.0 <= layout.height - MARGIN);
+        }
+    }
+
+    #[test]
+    fn group_mappings_into_edges() {
+        let mut alias = mapping(ROOT, "test:name-alias", KindId::STRING);
+        alias["metadata"]["infoapi/alias-of"] = "test:name".into();
+        let schema = fixture(vec![
+            mapping(ROOT, "test:name", KindId::STRING),
+            mapping(ROOT, "test:nick", KindId::STRING),
+            implicit(ROOT, "test:to-string", KindId::STRING),
+            alias,
+            mapping(PLAYER, "test:same", PLAYER),
+        ]);
+        let layout = layout(&schema, &PluginFilter::from_iter([]));
+
+        let edges: Vec<_> = layout
+            .edges
+            .iter()
+            .map(|edge| {
+                let names: Vec<_> = edge.mappings.iter().map(|m| m.name.0.as_str()).collect();
+                (
+                    edge.from.0.as_str(),
+                    edge.to.0.as_str(),
+                    edge.is_implicit,
+                    names,
+                )
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                (PLAYER, PLAYER, false, vec!["test:same"]),
+                (ROOT, KindId::STRING, false, vec!["test:name", "test:nick"]),
+                (ROOT, KindId::STRING, true, vec!["test:to-string"]),
+            ]
+        );
+        assert!(layout.edges.iter().all(|edge| edge.path.starts_with("M ")));
+        assert_ne!(layout.edges[1].path, layout.edges[2].path);
+    }
+
+    #[test]
+    fn hide_filtered_plugins() {
+        let mut hidden = mapping(ROOT, "hidden:player", PLAYER);
+        hidden["metadata"]["infoapi/source-plugin"] = "Hidden".into();
+        let mut schema = fixture(vec![hidden, mapping(ROOT, "test:name", KindId::STRING)]);
+        let player = schema.kinds.get_mut(&kind(PLAYER)).unwrap();
+        player.metadata.source_plugin = Some("Hidden".into());
+
+        let layout = layout(&schema, &PluginFilter::from_iter(["Hidden".to_string()]));
+        assert_eq!(layout.edges.len(), 1);
+        assert_eq!(layout.edges[0].mappings[0].name.0, "test:name");
+        assert_eq!(layers(&layout), [(KindId::STRING, 1), (ROOT, 0)]);
+        assert!(layout.node(&kind(PLAYER)).is_none());
+    }
+}
@@ -0,0 +1,267 @@
+//! Reports inconsistencies in the loaded schema, such as references to kinds that are not defined.
+
+use std::collections::BTreeSet;
+
+use crate::{
+    data::{All, KindId, MappingDef, MappingName},
+    diagnostics::Severity,
+};
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub category: Category,
+    /// The kind or mapping that the issue is about.
+    pub subject: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    DanglingKind,
+    DanglingAlias,
+    EmptyRoot,
+    Undisplayable,
+}
+
+impl Category {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::DanglingKind => "References to undefined kinds",
+            Self::DanglingAlias => "Aliases of undefined mappings",
+            Self::EmptyRoot => "Root kinds without mappings",
+            Self::Undisplayable => "Kinds that cannot be displayed",
+        }
+    }
+}
+
+/// Checks the whole schema, ordered by category.
+pub fn check(schema: &All) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    for mapping in schema
+        .mappings
+        .values()
+        .flat_map(|mappings| mappings.values())
+    {
+        check_mapping(schema, mapping, &mut issues);
+    }
+
+    for (kind, def) in &schema.kinds {
+        let has_mappings = schema
+            .mappings
+            .get(kind)
+            .is_some_and(|mappings| !mappings.is_empty());
+        if def.metadata.is_root && !has_mappings {
+            issues.push(Issue {
+                severity: Severity::Warning,
+                category: Category::EmptyRoot,
+                subject: kind.0.clone(),
+                message: "this template kind has no mappings, so templates cannot use anything"
+                    .into(),
+            });
+        }
+    }
+
+    let displayable = reaches_displayable(schema);
+    for kind in schema.kinds.keys() {
+        if !displayable.contains(kind) {
+            issues.push(Issue {
+                severity: Severity::Warning,
+                category: Category::Undisplayable,
+                subject: kind.0.clone(),
+                message: "no chain of mappings from this kind leads to a displayable kind".into(),
+            });
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.category);
+    issues
+}
+
+fn check_mapping(schema: &All, mapping: &MappingDef, issues: &mut Vec<Issue>) {
+    let subject = format!("{} on {}", mapping.name.0, mapping.source_kind.0);
+    let mut dangling = |role: String, kind: &KindId| {
+        if !schema.kinds.contains_key(kind) {
+            issues.push(Issue {
+                severity: Severity::Error,
+                category: Category::DanglingKind,
+                subject: subject.clone(),
+                message: format!("{role} {} is not defined", kind.0),
+            });
+        }
+    };
+
+    dangling("source kind".into(), &mapping.source_kind);
+    dangling("target kind".into(), &mapping.target_kind);
+    for param in &mapping.parameters {
+        dangling(format!("kind of parameter `{}`", param.name.0), &param.kind);
+    }
+
+    if let Some(alias_of) = &mapping.metadata.alias_of {
+        // aliases are registered on the same source kind as the canonical mapping
+        let exists = schema
+            .mappings
+            .get(&mapping.source_kind)
+            .is_some_and(|mappings| mappings.contains_key(&MappingName(alias_of.clone())));
+        if !exists {
+            issues.push(Issue {
+                severity: Severity::Error,
+                category: Category::DanglingAlias,
+                subject,
+                message: format!(
+                    "alias of {alias_of}, which is not a mapping on {}",
+                    mapping.source_kind.0
+                ),
+            });
+        }
+    }
+}
+
+/// Finds the kinds that can be converted into a displayable kind through any chain of mappings.
+fn reaches_displayable(schema: &All) -> BTreeSet<KindId> {
+    let mut reachable: BTreeSet<_> = schema
+        .kinds
+        .iter()
+        .filter(|(_, def)| def.can_display)
+        .map(|(kind, _)| kind.clone())
+        .collect();
+
+    // propagate backwards along mappings until no more kinds are added
+    loop {
+        let mut changed = false;
+        for mapping in schema
+            .mappings
+            .values()
+            .flat_map(|mappings| mappings.values())
+        {
+            if reachable.contains(&mapping.target_kind)
+                && reachable.insert(mapping.source_kind.clone())
+            {
+                changed = true;
+            }
+        }
+        if !changed {
+            return reachable;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{mapping, param, schema, with_params};
+
+    const ROOT: &str = "test/root";
+
+    /// The category, subject and message of each issue, in the order reported.
+    fn summary(issues: &[Issue]) -> Vec<(Category, &str, &str)> {
+        issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.category,
+                    issue.subject.as_str(),
+                    issue.message.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consistent_schema() {
+        let schema = schema(
+            &[(ROOT, false), (KindId::STRING, true)],
+            vec![mapping(ROOT, "test:name", KindId::STRING)],
+        );
+        assert!(check(&schema).is_empty());
+    }
+
+    #[test]
+    fn dangling_kinds() {
+        let schema = schema(
+            &[(ROOT, false), (KindId::STRING, true)],
+            vec![
+                with_params(
+                    mapping(ROOT, "test:name", KindId::STRING),
+                    vec![param("format", "test/format", true, false)],
+                ),
+                mapping("test/ghost", "test:ghost", KindId::STRING),
+            ],
+        );
+        let issues = check(&schema);
+        assert!(issues.iter().all(|issue| issue.severity == Severity::Error));
+        assert_eq!(
+            summary(&issues),
+            [
+                (
+                    Category::DanglingKind,
+                    "test:ghost on test/ghost",
+                    "source kind test/ghost is not defined"
+                ),
+                (
+                    Category::DanglingKind,
+                    "test:name on test/root",
+                    "kind of parameter `format` test/format is not defined"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn dangling_alias() {
+        let mut nick = mapping(ROOT, "test:nick", KindId::STRING);
+        nick["metadata"]["infoapi/alias-of"] = "test:name".into();
+        let schema = schema(&[(ROOT, false), (KindId::STRING, true)], vec![nick]);
+        assert_eq!(
+            summary(&check(&schema)),
+            [(
+                Category::DanglingAlias,
+                "test:nick on test/root",
+                "alias of test:name, which is not a mapping on test/root"
+            )]
+        );
+    }
+
+    #[test]
+    fn empty_root() {
+        let mut schema = schema(&[(ROOT, true)], Vec::new());
+        let root = schema.kinds.get_mut(&KindId(ROOT.into())).unwrap();
+        root.metadata.is_root = true;
+        assert_eq!(
+            summary(&check(&schema)),
+            [(
+                Category::EmptyRoot,
+                ROOT,
+                "this template kind has no mappings, so templates cannot use anything"
+            )]
+        );
+    }
+
+    #[test]
+    fn undisplayable_kinds() {
+        const PLAYER: &str = "test/player";
+        let cyclic = schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![
+                mapping(ROOT, "test:player", PLAYER),
+                mapping(ROOT, "test:name", KindId::STRING),
+                mapping(PLAYER, "test:root", ROOT),
+            ],
+        );
+        assert!(check(&cyclic).is_empty(), "cycles reach displayable kinds");
+
+        let schema = schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![mapping(ROOT, "test:player", PLAYER)],
+        );
+        let message = "no chain of mappings from this kind leads to a displayable kind";
+        assert_eq!(
+            summary(&check(&schema)),
+            [
+                (Category::Undisplayable, PLAYER, message),
+                (Category::Undisplayable, ROOT, message),
+            ]
+        );
+    }
+}
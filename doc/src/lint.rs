@@ -0,0 +1,326 @@
+//! The `infoapi-lint` command line tool, which checks templates against exported schemas.
+//!
+//! Exits with 1 if any template has errors, or 2 if the input cannot be read.
+
+use std::{fs, process::ExitCode};
+
+use anyhow::Context as _;
+
+use crate::{
+    cli::{Arg, Args},
+    data::{self, All, KindId},
+    diagnostics::{self, Diagnostic, Severity},
+    parser::carets,
+};
+
+const USAGE: &str = "\
+Usage: infoapi-lint [OPTIONS] --schema <FILE>... [TEMPLATE]...
+
+Checks InfoAPI templates against schemas exported by `Doc::export`, e.g. through dump-infos.php.
+
+Arguments:
+  [TEMPLATE]...          Template strings to check
+
+Options:
+  -s, --schema <FILE>    A schema JSON file, may be repeated to merge multiple schemas
+  -k, --kind <KIND>      The root kind of the templates,
+                         required unless the schemas define exactly one template kind
+  -f, --file <FILE>      A file containing one template, may be repeated
+  -t, --template <TEXT>  A template string, same as a positional argument
+      --deny-warnings    Also fail if any template has warnings
+  -h, --help             Print this message
+";
+
+pub fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    schemas: Vec<String>,
+    kind: Option<String>,
+    inputs: Vec<Input>,
+    deny_warnings: bool,
+}
+
+enum Input {
+    File(String),
+    Text(String),
+}
+
+impl Options {
+    /// Parses the command line arguments, or returns `None` if help is requested.
+    fn parse(args: impl Iterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        let mut options = Self::default();
+        let mut args = Args::new(args);
+
+        while let Some(arg) = args.next()? {
+            let flag = match arg {
+                Arg::Positional(template) => {
+                    options.inputs.push(Input::Text(template));
+                    continue;
+                }
+                Arg::Flag(flag) => flag,
+            };
+
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-s" | "--schema" => options.schemas.push(args.value()?),
+                "-k" | "--kind" => options.kind = Some(args.value()?),
+                "-f" | "--file" => options.inputs.push(Input::File(args.value()?)),
+                "-t" | "--template" => options.inputs.push(Input::Text(args.value()?)),
+                "--deny-warnings" => options.deny_warnings = true,
+                _ => anyhow::bail!("unknown option {flag}"),
+            }
+        }
+
+        anyhow::ensure!(!options.schemas.is_empty(), "no schema files given");
+        anyhow::ensure!(!options.inputs.is_empty(), "no templates given");
+        Ok(Some(options))
+    }
+}
+
+/// Checks all templates and prints the diagnostics.
+///
+/// Returns whether all templates passed.
+fn run(options: &Options) -> anyhow::Result<bool> {
+    let schema = data::load_files(&options.schemas)?;
+    let source_kind = root_kind(&schema, options.kind.as_deref())?;
+
+    let mut errors = 0;
+    let mut warnings = 0;
+    let mut text_count = 0;
+
+    for input in &options.inputs {
+        let (name, template) = match input {
+            Input::File(path) => {
+                let template =
+                    fs::read_to_string(path).with_context(|| format!("read template {path}"))?;
+                (path.clone(), template)
+            }
+            Input::Text(template) => {
+                text_count += 1;
+                (format!("template #{text_count}"), template.clone())
+            }
+        };
+
+        for diagnostic in diagnostics::check(&schema, &source_kind, &template) {
+            match diagnostic.severity {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+            eprintln!("{}", render(&diagnostic, &name, &template));
+        }
+    }
+
+    eprintln!(
+        "checked {} templates: {errors} errors, {warnings} warnings",
+        options.inputs.len()
+    );
+    Ok(errors == 0 && !(options.deny_warnings && warnings > 0))
+}
+
+fn root_kind(schema: &All, kind: Option<&str>) -> anyhow::Result<KindId> {
+    let root_kinds: Vec<_> = schema
+        .kinds
+        .iter()
+        .filter(|(_, def)| def.metadata.is_root)
+        .map(|(kind, _)| kind)
+        .collect();
+
+    match (kind, &root_kinds[..]) {
+        (Some(kind), _) => {
+            let kind = KindId(kind.to_string());
+            anyhow::ensure!(
+                schema.kinds.contains_key(&kind),
+                "the schemas do not define the kind {}",
+                kind.0
+            );
+            Ok(kind)
+        }
+        (None, [kind]) => Ok((*kind).clone()),
+        (None, _) => {
+            let names: Vec<_> = root_kinds.iter().map(|kind| kind.0.as_str()).collect();
+            anyhow::bail!(
+                "--kind is required to choose one of the template kinds: {}",
+                names.join(", ")
+            )
+        }
+    }
+}
+
+/// Formats a diagnostic with the line of the template that it refers to,
+/// since template files may span multiple lines.
+fn render(diagnostic: &Diagnostic, name: &str, template: &str) -> String {
+    let severity = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+
+    let start = diagnostic.span.start;
+    let line_start = template[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = template[start..]
+        .find('\n')
+        .map_or(template.len(), |index| start + index);
+    let line = &template[line_start..line_end];
+    let line_number = template[..start].matches('\n').count() + 1;
+    let column = template[line_start..start].chars().count() + 1;
+    let end = diagnostic.span.end.clamp(start, line_end);
+
+    let gutter = " ".repeat(line_number.to_string().len());
+    format!(
+        "{severity}: {message}\n{gutter}--> {name}:{line_number}:{column}\n{gutter} |\n{line_number} | {line}\n{gutter} | {carets}\n",
+        message = diagnostic.message,
+        carets = carets(line, start - line_start..end - line_start),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::schema;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Option<Options>> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn inputs(options: &Options) -> Vec<String> {
+        options
+            .inputs
+            .iter()
+            .map(|input| match input {
+                Input::File(path) => format!("file {path}"),
+                Input::Text(text) => format!("text {text}"),
+            })
+            .collect()
+    }
+
+    /// A schema with a template kind for each of `root_kinds`, plus a kind that is not a template kind.
+    fn with_roots(root_kinds: &[&str]) -> All {
+        let mut kinds: Vec<_> = root_kinds.iter().map(|&kind| (kind, false)).collect();
+        kinds.push((KindId::STRING, true));
+        let mut schema = schema(&kinds, Vec::new());
+        for kind in root_kinds {
+            let def = schema.kinds.get_mut(&KindId(kind.to_string())).unwrap();
+            def.metadata.is_root = true;
+        }
+        schema
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = parse(&[
+            "-s",
+            "a.json",
+            "--schema=b.json",
+            "--kind",
+            "test/root",
+            "{name}",
+            "-f",
+            "template.txt",
+            "--template={xp}",
+            "--deny-warnings",
+            "--",
+            "-h",
+        ])
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(options.schemas, ["a.json", "b.json"]);
+        assert_eq!(options.kind.as_deref(), Some("test/root"));
+        assert_eq!(
+            inputs(&options),
+            ["text {name}", "file template.txt", "text {xp}", "text -h"]
+        );
+        assert!(options.deny_warnings);
+    }
+
+    #[test]
+    fn parse_help() {
+        assert!(parse(&["-s", "a.json", "--help", "--unknown"])
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let message = |args: &[&str]| match parse(args) {
+            Ok(_) => panic!("{args:?} should be rejected"),
+            Err(err) => err.to_string(),
+        };
+
+        assert_eq!(message(&["{name}"]), "no schema files given");
+        assert_eq!(message(&["-s", "a.json"]), "no templates given");
+        assert_eq!(message(&["-s", "a.json", "-x"]), "unknown option -x");
+        assert_eq!(message(&["{name}", "-s"]), "-s requires a value");
+        assert_eq!(
+            message(&["--deny-warnings=yes"]),
+            "--deny-warnings does not take a value"
+        );
+    }
+
+    #[test]
+    fn root_kind_defaults_to_the_only_template_kind() {
+        let schema = with_roots(&["test/root"]);
+        assert_eq!(root_kind(&schema, None).unwrap().0, "test/root");
+        assert_eq!(
+            root_kind(&schema, Some(KindId::STRING)).unwrap().0,
+            KindId::STRING
+        );
+        assert_eq!(
+            root_kind(&schema, Some("test/missing"))
+                .unwrap_err()
+                .to_string(),
+            "the schemas do not define the kind test/missing"
+        );
+    }
+
+    #[test]
+    fn root_kind_must_be_chosen() {
+        let schema = with_roots(&["test/a", "test/b"]);
+        assert_eq!(
+            root_kind(&schema, None).unwrap_err().to_string(),
+            "--kind is required to choose one of the template kinds: test/a, test/b"
+        );
+        assert_eq!(root_kind(&schema, Some("test/b")).unwrap().0, "test/b");
+    }
+
+    #[test]
+    fn render_line_of_diagnostic() {
+        let diagnostic = Diagnostic {
+            severity: Severity::Error,
+            message: "no mapping called `nmae` for test/root".into(),
+            span: 13..17,
+        };
+        let template = "Hello\n\u{e9}t\u{e9} {nmae}\nbye";
+        assert_eq!(
+            render(&diagnostic, "greeting.txt", template),
+            "\
+error: no mapping called `nmae` for test/root
+ --> greeting.txt:2:6
+  |
+2 | \u{e9}t\u{e9} {nmae}
+  |      ^^^^
+"
+        );
+    }
+}
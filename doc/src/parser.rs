@@ -0,0 +1,178 @@
+use std::{fmt, ops::Range};
+
+/// Whitespace characters skipped between tokens, same as `StringParser::skipWhitespace`.
+const WHITESPACE: &[char] = &[' ', '\t', '\n', '\r', '\u{b}'];
+
+/// Port of the internal `StringParser` util in `lib/parser.php`.
+///
+/// Positions are byte offsets into the buffer.
+pub struct StringParser<'t> {
+    buf: &'t str,
+    pub pos: usize,
+}
+
+impl<'t> StringParser<'t> {
+    pub fn new(buf: &'t str) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    pub fn span_error(&self, why: impl Into<String>, start: usize, end: usize) -> ParseError {
+        ParseError {
+            why: why.into(),
+            buf: self.buf.to_string(),
+            start,
+            end: end.min(self.buf.len()).max(start),
+        }
+    }
+
+    /// Creates an error spanning one character at the current position.
+    pub fn error_here(&self, why: impl Into<String>) -> ParseError {
+        let len = self.rest().chars().next().map_or(1, char::len_utf8);
+        self.span_error(why, self.pos, self.pos + len)
+    }
+
+    pub fn eof(&self) -> bool {
+        self.pos == self.buf.len()
+    }
+
+    pub fn rest(&self) -> &'t str {
+        &self.buf[self.pos..]
+    }
+
+    pub fn peek_starts_with(&self, text: &str) -> bool {
+        self.rest().starts_with(text)
+    }
+
+    pub fn read_exact_text(&mut self, text: &str) -> bool {
+        if self.peek_starts_with(text) {
+            self.pos += text.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reads until the first occurrence of any of the needles, or returns `None` if none are found.
+    pub fn read_until(&mut self, needles: &[char]) -> Option<&'t str> {
+        let offset = self.rest().find(needles)?;
+        let ret = &self.rest()[..offset];
+        self.pos += offset;
+        Some(ret)
+    }
+
+    /// Reads the longest prefix of characters satisfying `pred`.
+    pub fn read_charset(&mut self, pred: impl Fn(char) -> bool) -> &'t str {
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        self.read_charset(|c| WHITESPACE.contains(&c));
+    }
+
+    /// Executes `run` and rolls back the parser if it returns false.
+    pub fn try_with(&mut self, run: impl FnOnce(&mut Self) -> bool) {
+        let initial = self.pos;
+        if !run(self) {
+            self.pos = initial;
+        }
+    }
+
+    pub fn read_all(&mut self) -> &'t str {
+        let ret = self.rest();
+        self.pos = self.buf.len();
+        ret
+    }
+}
+
+/// Port of `ParseException` in `lib/parser.php`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub why: String,
+    pub buf: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ParseError {
+    /// A line of carets pointing at the error span under `buf`.
+    pub fn carets(&self) -> String {
+        carets(&self.buf, self.start..self.end)
+    }
+}
+
+/// Renders a line of carets pointing at `span` when printed under `buf`.
+pub fn carets(buf: &str, span: Range<usize>) -> String {
+    let indent = buf[..span.start].chars().count();
+    let width = buf[span].chars().count().max(1);
+    format!("{}{}", " ".repeat(indent), "^".repeat(width))
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n{}\n{}", self.why, self.buf, self.carets())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_exact_text_advances_on_match() {
+        let mut parser = StringParser::new("abc");
+        assert!(parser.read_exact_text("a"));
+        assert!(!parser.read_exact_text("a"));
+        assert!(!parser.read_exact_text("bcd"));
+        assert!(parser.read_exact_text("bc"));
+        assert!(!parser.read_exact_text("d"));
+        assert!(parser.eof());
+    }
+
+    #[test]
+    fn read_until_any_char() {
+        let mut parser = StringParser::new("abcdabcdabcd");
+        assert_eq!(parser.read_until(&['a']), Some(""));
+        assert_eq!(parser.read_until(&['b']), Some("a"));
+        assert_eq!(parser.read_until(&['d', 'a']), Some("bc"));
+        assert_eq!(parser.read_until(&['b', 'a']), Some("d"));
+        assert_eq!(parser.read_until(&['x']), None);
+        assert_eq!(parser.read_all(), "abcdabcd");
+    }
+
+    #[test]
+    fn skip_whitespace_positions() {
+        let mut parser = StringParser::new("a   b\t \nc");
+        parser.skip_whitespace();
+        assert_eq!(parser.pos, 0);
+        assert!(parser.read_exact_text("a"));
+        parser.skip_whitespace();
+        assert_eq!(parser.pos, 4);
+        assert!(parser.read_exact_text("b"));
+        parser.skip_whitespace();
+        assert_eq!(parser.pos, 8);
+        assert!(parser.read_exact_text("c"));
+        parser.skip_whitespace();
+        assert_eq!(parser.pos, 9);
+    }
+
+    #[test]
+    fn try_with_rolls_back() {
+        let mut parser = StringParser::new("abc");
+        parser.try_with(|parser| parser.read_exact_text("ab") && parser.read_exact_text("x"));
+        assert_eq!(parser.pos, 0);
+        parser.try_with(|parser| parser.read_exact_text("ab"));
+        assert_eq!(parser.pos, 2);
+    }
+
+    #[test]
+    fn carets_count_chars() {
+        assert_eq!(carets("ab", 1..2), " ^");
+        assert_eq!(carets("éab", 2..4), " ^^");
+        assert_eq!(carets("ab", 2..2), "  ^");
+    }
+}
@@ -0,0 +1,496 @@
+//! Port of the path finder in `lib/pathfind.php`.
+
+#[cfg(feature = "web")]
+use std::collections::{btree_map, BTreeMap, VecDeque};
+use std::{
+    cmp,
+    collections::{BTreeSet, BinaryHeap},
+    sync::Arc,
+};
+
+use crate::data::{All, KindId, MappingDef, QualifiedRef};
+
+/// Cost of a path.
+///
+/// A path with fewer steps is better than a path with more steps.
+/// If two paths have the same number of steps,
+/// a path with lower score is better than a path with higher score.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cost {
+    pub num_mappings: usize,
+    pub sum_score: usize,
+}
+
+impl Cost {
+    fn add_mapping(self, score: usize) -> Self {
+        Self {
+            num_mappings: self.num_mappings + 1,
+            sum_score: self.sum_score + score,
+        }
+    }
+}
+
+/// A mapping in a path.
+#[derive(Debug, Clone)]
+pub struct Hop {
+    pub mapping: Arc<MappingDef>,
+    /// The index of the call that this mapping resolves,
+    /// or `None` if it is an implicit mapping inserted by the server.
+    pub call: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Path {
+    /// The number of calls that have been resolved.
+    pub read_calls: usize,
+    pub tail_kind: KindId,
+    pub hops: Vec<Hop>,
+    implicit_loop_detector: BTreeSet<KindId>,
+    pub cost: Cost,
+}
+
+impl Path {
+    /// The hop that resolves the call with index `call`.
+    pub fn hop_for_call(&self, call: usize) -> &Hop {
+        self.hops
+            .iter()
+            .find(|hop| hop.call == Some(call))
+            .expect("accepted paths resolve all calls")
+    }
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+impl Eq for Path {}
+impl PartialOrd for Path {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Path {
+    // reversed so that `BinaryHeap` pops the cheapest path first
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        other.cost.cmp(&self.cost)
+    }
+}
+
+/// The implicit mappings from `kind`.
+pub fn implicit_mappings<'t>(
+    schema: &'t All,
+    kind: &KindId,
+) -> impl Iterator<Item = &'t Arc<MappingDef>> {
+    schema
+        .mappings
+        .get(kind)
+        .into_iter()
+        .flat_map(|mappings| mappings.values())
+        .filter(|mapping| mapping.is_implicit)
+}
+
+/// Finds the shortest chain of implicit mappings from `kind` to each kind reachable through implicit mappings.
+///
+/// `kind` itself is included with an empty chain.
+#[cfg(feature = "web")]
+pub fn implicit_routes(schema: &All, kind: &KindId) -> BTreeMap<KindId, Vec<Arc<MappingDef>>> {
+    let mut routes = BTreeMap::from([(kind.clone(), Vec::new())]);
+    let mut queue = VecDeque::from([kind.clone()]);
+
+    while let Some(kind) = queue.pop_front() {
+        let route = routes[&kind].clone();
+        for implicit in implicit_mappings(schema, &kind) {
+            if let btree_map::Entry::Vacant(entry) = routes.entry(implicit.target_kind.clone()) {
+                let mut route = route.clone();
+                route.push(implicit.clone());
+                entry.insert(route);
+                queue.push_back(implicit.target_kind.clone());
+            }
+        }
+    }
+
+    routes
+}
+
+/// Enumerates chains of mappings from `source_kind` to a kind accepted by `admit_kind`, shortest first.
+///
+/// Chains never visit the same kind twice and contain at most `max_len` mappings.
+/// Only mappings accepted by `use_mapping` are considered.
+/// At most `limit` chains are returned.
+#[cfg(feature = "web")]
+pub fn routes_to(
+    schema: &All,
+    source_kind: &KindId,
+    admit_kind: impl Fn(&KindId) -> bool,
+    use_mapping: impl Fn(&MappingDef) -> bool,
+    max_len: usize,
+    limit: usize,
+) -> Vec<Vec<Arc<MappingDef>>> {
+    let mut routes = Vec::new();
+    let mut queue = VecDeque::from([(source_kind.clone(), Vec::<Arc<MappingDef>>::new())]);
+
+    while let Some((kind, route)) = queue.pop_front() {
+        if route.len() >= max_len {
+            continue;
+        }
+
+        let mappings = schema
+            .mappings
+            .get(&kind)
+            .into_iter()
+            .flat_map(|mappings| mappings.values());
+        for mapping in mappings {
+            let target = &mapping.target_kind;
+            let revisits =
+                target == source_kind || route.iter().any(|hop| &hop.target_kind == target);
+            if revisits || !use_mapping(mapping) {
+                continue;
+            }
+
+            let mut route = route.clone();
+            route.push(mapping.clone());
+            if admit_kind(target) {
+                routes.push(route.clone());
+                if routes.len() >= limit {
+                    return routes;
+                }
+            }
+            queue.push_back((target.clone(), route));
+        }
+    }
+
+    routes
+}
+
+/// Performs Dijkstra pathfinding on the shortest path with the cost function defined in `Cost`.
+///
+/// All accepted paths are returned, cheapest first.
+pub fn find(
+    schema: &All,
+    calls: &[QualifiedRef],
+    source_kind: &KindId,
+    admit_tail_kind: impl Fn(&KindId) -> bool,
+) -> Vec<Path> {
+    let mut heap = BinaryHeap::new();
+    heap.push(Path {
+        read_calls: 0,
+        tail_kind: source_kind.clone(),
+        hops: Vec::new(),
+        implicit_loop_detector: BTreeSet::from([source_kind.clone()]),
+        cost: Cost::default(),
+    });
+
+    let mut accepted = Vec::new();
+    while let Some(path) = heap.pop() {
+        let mut new_paths = Vec::new();
+
+        if let Some(call) = calls.get(path.read_calls) {
+            let named = schema
+                .mappings
+                .get(&path.tail_kind)
+                .into_iter()
+                .flat_map(|mappings| mappings.values());
+            for mapping in named {
+                let Some(score) = mapping.name.matches(call) else {
+                    continue;
+                };
+
+                let mut hops = path.hops.clone();
+                hops.push(Hop {
+                    mapping: mapping.clone(),
+                    call: Some(path.read_calls),
+                });
+                new_paths.push(Path {
+                    read_calls: path.read_calls + 1,
+                    tail_kind: mapping.target_kind.clone(),
+                    hops,
+                    implicit_loop_detector: BTreeSet::from([mapping.target_kind.clone()]),
+                    cost: path.cost.add_mapping(score),
+                });
+            }
+        }
+
+        for implicit in implicit_mappings(schema, &path.tail_kind) {
+            if path.implicit_loop_detector.contains(&implicit.target_kind) {
+                continue;
+            }
+
+            let mut hops = path.hops.clone();
+            hops.push(Hop {
+                mapping: implicit.clone(),
+                call: None,
+            });
+            let mut implicit_loop_detector = path.implicit_loop_detector.clone();
+            implicit_loop_detector.insert(implicit.target_kind.clone());
+            new_paths.push(Path {
+                read_calls: path.read_calls, // the call was not consumed
+                tail_kind: implicit.target_kind.clone(),
+                hops,
+                implicit_loop_detector,
+                cost: path.cost.add_mapping(0),
+            });
+        }
+
+        for new_path in new_paths {
+            if new_path.read_calls == calls.len() && admit_tail_kind(&new_path.tail_kind) {
+                accepted.push(new_path);
+            } else {
+                heap.push(new_path);
+            }
+        }
+    }
+
+    accepted.sort_by_key(|path| path.cost);
+    accepted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{implicit, mapping, schema};
+
+    const ROOT: &str = "test/root";
+    const PLAYER: &str = "test/player";
+
+    fn kind(id: &str) -> KindId {
+        KindId(id.into())
+    }
+
+    fn calls(names: &[&str]) -> Vec<QualifiedRef> {
+        names.iter().map(|name| QualifiedRef::parse(name)).collect()
+    }
+
+    /// The mapping names of each path, with implicit mappings in brackets.
+    fn names(paths: &[Path]) -> Vec<Vec<String>> {
+        paths
+            .iter()
+            .map(|path| {
+                path.hops
+                    .iter()
+                    .map(|hop| match hop.call {
+                        Some(_) => hop.mapping.name.0.clone(),
+                        None => format!("[{}]", hop.mapping.name.0),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn cost(num_mappings: usize, sum_score: usize) -> Cost {
+        Cost {
+            num_mappings,
+            sum_score,
+        }
+    }
+
+    #[test]
+    fn rank_by_mappings_then_score() {
+        let schema = schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![
+                mapping(ROOT, "a:b:name", KindId::STRING),
+                mapping(ROOT, "c:name", KindId::STRING),
+                implicit(ROOT, "test:player", PLAYER),
+                mapping(PLAYER, "name", KindId::STRING),
+            ],
+        );
+        let paths = find(&schema, &calls(&["name"]), &kind(ROOT), |kind| {
+            kind.0 == KindId::STRING
+        });
+
+        assert_eq!(
+            names(&paths),
+            [
+                vec!["c:name"],
+                vec!["a:b:name"],
+                vec!["[test:player]", "name"]
+            ]
+        );
+        let costs: Vec<_> = paths.iter().map(|path| path.cost).collect();
+        assert_eq!(costs, [cost(1, 1), cost(1, 2), cost(2, 0)]);
+    }
+
+    #[test]
+    fn keep_ties() {
+        let schema = schema(
+            &[(ROOT, false), (KindId::STRING, true)],
+            vec![
+                mapping(ROOT, "a:name", KindId::STRING),
+                mapping(ROOT, "b:name", KindId::STRING),
+            ],
+        );
+        let paths = find(&schema, &calls(&["name"]), &kind(ROOT), |_| true);
+
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].cost, paths[1].cost);
+        let mut names = names(&paths);
+        names.sort();
+        assert_eq!(names, [["a:name"], ["b:name"]]);
+    }
+
+    #[test]
+    fn insert_implicit_mappings_before_and_after_calls() {
+        const NAME: &str = "test/name";
+        let schema = schema(
+            &[
+                (ROOT, false),
+                (PLAYER, false),
+                (NAME, false),
+                (KindId::STRING, true),
+            ],
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                mapping(PLAYER, "test:name", NAME),
+                implicit(NAME, "test:name-string", KindId::STRING),
+            ],
+        );
+        let paths = find(&schema, &calls(&["name"]), &kind(ROOT), |kind| {
+            kind.0 == KindId::STRING
+        });
+
+        assert_eq!(
+            names(&paths),
+            [["[test:player]", "test:name", "[test:name-string]"]]
+        );
+        assert_eq!(paths[0].cost, cost(3, 1));
+        assert_eq!(paths[0].hop_for_call(0).mapping.name.0, "test:name");
+    }
+
+    #[test]
+    fn implicit_cycles_terminate() {
+        let schema = schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                implicit(PLAYER, "test:root", ROOT),
+                mapping(PLAYER, "test:name", KindId::STRING),
+                mapping(KindId::STRING, "test:self", KindId::STRING),
+            ],
+        );
+
+        let paths = find(&schema, &calls(&["name"]), &kind(ROOT), |_| true);
+        assert_eq!(names(&paths), [["[test:player]", "test:name"]]);
+
+        // a named call resets the loop detector, but each call is consumed only once
+        let paths = find(
+            &schema,
+            &calls(&["self", "self"]),
+            &kind(KindId::STRING),
+            |_| true,
+        );
+        assert_eq!(names(&paths), [["test:self", "test:self"]]);
+
+        let paths = find(&schema, &calls(&["missing"]), &kind(ROOT), |_| true);
+        assert!(paths.is_empty());
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn implicit_routes_are_shortest() {
+        const NAME: &str = "test/name";
+        let schema = schema(
+            &[(ROOT, false), (PLAYER, false), (NAME, false)],
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                implicit(PLAYER, "test:root", ROOT),
+                implicit(PLAYER, "test:player-name", NAME),
+                implicit(ROOT, "test:name", NAME),
+            ],
+        );
+        let routes = implicit_routes(&schema, &kind(ROOT));
+
+        let lens: Vec<_> = routes
+            .iter()
+            .map(|(kind, route)| (kind.0.as_str(), route.len()))
+            .collect();
+        assert_eq!(lens, [(NAME, 1), (PLAYER, 1), (ROOT, 0)]);
+        assert_eq!(routes[&kind(NAME)][0].name.0, "test:name");
+    }
+
+    #[cfg(feature = "web")]
+    fn route_names(routes: &[Vec<Arc<MappingDef>>]) -> Vec<Vec<&str>> {
+        routes
+            .iter()
+            .map(|route| {
+                route
+                    .iter()
+                    .map(|mapping| mapping.name.0.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "web")]
+    fn route_fixture() -> All {
+        schema(
+            &[(ROOT, false), (PLAYER, false), (KindId::STRING, true)],
+            vec![
+                implicit(ROOT, "test:player", PLAYER),
+                implicit(PLAYER, "test:root", ROOT),
+                mapping(ROOT, "test:name", KindId::STRING),
+                mapping(PLAYER, "test:nick", KindId::STRING),
+            ],
+        )
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn routes_to_shortest_first() {
+        let schema = route_fixture();
+        let routes = routes_to(
+            &schema,
+            &kind(ROOT),
+            |kind| kind.0 == KindId::STRING,
+            |_| true,
+            3,
+            10,
+        );
+        assert_eq!(
+            route_names(&routes),
+            [vec!["test:name"], vec!["test:player", "test:nick"]]
+        );
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn routes_to_respects_limits() {
+        let schema = route_fixture();
+        let admit = |kind: &KindId| kind.0 == KindId::STRING;
+
+        let routes = routes_to(&schema, &kind(ROOT), admit, |_| true, 3, 1);
+        assert_eq!(route_names(&routes), [["test:name"]]);
+
+        let routes = routes_to(&schema, &kind(ROOT), admit, |_| true, 1, 10);
+        assert_eq!(route_names(&routes), [["test:name"]]);
+
+        let routes = routes_to(
+            &schema,
+            &kind(ROOT),
+            admit,
+            |mapping| mapping.name.0 != "test:name",
+            3,
+            10,
+        );
+        assert_eq!(route_names(&routes), [["test:player", "test:nick"]]);
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn routes_to_skips_implicit_cycles() {
+        let schema = route_fixture();
+
+        let routes = routes_to(
+            &schema,
+            &kind(PLAYER),
+            |kind| kind.0 == ROOT,
+            |_| true,
+            10,
+            10,
+        );
+        assert_eq!(route_names(&routes), [["test:root"]]);
+
+        let routes = routes_to(&schema, &kind(ROOT), |_| false, |_| true, usize::MAX, 10);
+        assert!(routes.is_empty());
+    }
+}
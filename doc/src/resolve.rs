@@ -0,0 +1,314 @@
+//! Resolves parsed expressions against the loaded schema.
+
+use std::{collections::BTreeSet, fmt, slice};
+
+#[cfg(feature = "web")]
+use crate::data::FQN_SEPARATOR;
+use crate::{
+    ast,
+    data::{All, KindId, MappingDef, MappingName, ParamDef, QualifiedRef},
+    pathfind::{self, Hop},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveError {
+    /// No mappings reachable at the call with index `call` match `name`.
+    UnknownMapping {
+        call: usize,
+        name: QualifiedRef,
+        kind: KindId,
+    },
+    /// Multiple mappings match `name` equally well.
+    Ambiguous {
+        call: usize,
+        name: QualifiedRef,
+        kind: KindId,
+        candidates: Vec<MappingName>,
+    },
+    /// All calls resolve, but the result cannot be converted into an accepted kind.
+    Unadmitted { kind: KindId },
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownMapping { name, kind, .. } => {
+                write!(f, "no mapping called `{name}` for {}", kind.0)
+            }
+            Self::Ambiguous {
+                name,
+                kind,
+                candidates,
+                ..
+            } => {
+                let candidates: Vec<_> = candidates.iter().map(|name| name.0.as_str()).collect();
+                write!(
+                    f,
+                    "`{name}` for {} is ambiguous between {}",
+                    kind.0,
+                    candidates.join(", ")
+                )
+            }
+            Self::Unadmitted { kind } => {
+                write!(
+                    f,
+                    "the expression resolves to {}, which is not accepted here",
+                    kind.0
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+/// Resolves each call in `expr` starting from `source_kind` in the same way as `Template::resolveInfoPath`.
+///
+/// The returned hops include implicit mappings that the server inserts automatically.
+pub fn resolve_info_expr(
+    schema: &All,
+    source_kind: &KindId,
+    expr: &ast::InfoExpr,
+    admit_tail_kind: impl Fn(&KindId) -> bool,
+) -> Result<Vec<Hop>, ResolveError> {
+    let names: Vec<_> = expr.calls.iter().map(|call| call.name.clone()).collect();
+    let paths = pathfind::find(schema, &names, source_kind, &admit_tail_kind);
+
+    let Some(best) = paths.first() else {
+        return Err(diagnose(schema, source_kind, &names));
+    };
+
+    let ties: Vec<_> = paths
+        .iter()
+        .take_while(|path| path.cost == best.cost)
+        .collect();
+    for (call, name) in names.iter().enumerate() {
+        let candidates: BTreeSet<_> = ties
+            .iter()
+            .map(|path| path.hop_for_call(call).mapping.name.clone())
+            .collect();
+
+        if candidates.len() > 1 {
+            return Err(ResolveError::Ambiguous {
+                call,
+                name: name.clone(),
+                kind: best.hop_for_call(call).mapping.source_kind.clone(),
+                candidates: candidates.into_iter().collect(),
+            });
+        }
+    }
+
+    Ok(best.hops.clone())
+}
+
+/// Whether calling `name` after `kind` resolves to `mapping` alone on the server,
+/// possibly through implicit mappings.
+pub fn resolves_to(schema: &All, kind: &KindId, name: &QualifiedRef, mapping: &MappingDef) -> bool {
+    let paths = pathfind::find(schema, slice::from_ref(name), kind, |_| true);
+    let Some(best) = paths.first() else {
+        return false;
+    };
+
+    paths
+        .iter()
+        .take_while(|path| path.cost == best.cost)
+        .all(|path| {
+            let resolved = &path.hop_for_call(0).mapping;
+            resolved.source_kind == mapping.source_kind && resolved.name == mapping.name
+        })
+}
+
+/// The shortest name that resolves to `mapping` alone when called after `kind`,
+/// or the fully qualified name if no name does.
+///
+/// Mappings reached through fewer implicit mappings win regardless of the name,
+/// so each candidate is checked by resolving it from `kind` like the server.
+#[cfg(feature = "web")]
+pub fn shortest_name(schema: &All, kind: &KindId, mapping: &MappingDef) -> String {
+    // `MappingName::minify` usually finds the name, so it is tried first
+    let minified = match schema.mappings.get(&mapping.source_kind) {
+        Some(mappings) => mapping.name.minify(mappings.keys()),
+        None => mapping.name.0.clone(),
+    };
+
+    let tokens: Vec<_> = mapping.name.0.split(FQN_SEPARATOR).collect();
+    let (last, qualifiers) = tokens
+        .split_last()
+        .expect("split returns at least one token");
+    // subsets of the qualifiers in their original order, fewest first
+    let mut subsets: Vec<Vec<&str>> = (0..1_usize << qualifiers.len())
+        .map(|mask| {
+            let subset = qualifiers
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| mask & (1 << i) != 0)
+                .map(|(_, &token)| token);
+            subset.chain([*last]).collect()
+        })
+        .collect();
+    subsets.sort_by_key(Vec::len);
+
+    let mut candidates = [minified]
+        .into_iter()
+        .chain(subsets.into_iter().map(|subset| subset.join(FQN_SEPARATOR)));
+    candidates
+        .find(|name| resolves_to(schema, kind, &QualifiedRef::parse(name), mapping))
+        .unwrap_or_else(|| mapping.name.0.clone())
+}
+
+/// Why an argument is not passed to any parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnmatchedArg {
+    /// The mapping has no parameter with the name of the argument, so the server drops it.
+    UnknownName,
+    /// All parameters are taken by earlier arguments, so the server fails to load the template.
+    TooMany,
+}
+
+/// Arguments of a call assigned to the parameters of a mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedArgs {
+    /// The index of the parameter that each argument is passed to.
+    pub args: Vec<Result<usize, UnmatchedArg>>,
+    /// The index of the argument that each parameter receives, or `None` if it is not passed.
+    ///
+    /// This differs from `args` when a later argument overwrites an earlier one.
+    pub params: Vec<Option<usize>>,
+}
+
+/// Assigns arguments to parameters in the same way as `Template::matchArgsToParams`.
+///
+/// `arg_names` contains the name of each argument, or `None` for positional arguments.
+/// A positional argument is passed to the first parameter not taken by an earlier argument.
+/// The server does not support multiple values for `multi` parameters yet,
+/// so each parameter receives only the last argument passed to it.
+pub fn match_args_to_params<'t>(
+    params: &[ParamDef],
+    arg_names: impl IntoIterator<Item = Option<&'t str>>,
+) -> MatchedArgs {
+    let mut taken = vec![false; params.len()];
+    let mut matched = MatchedArgs {
+        args: Vec::new(),
+        params: vec![None; params.len()],
+    };
+
+    for (arg, name) in arg_names.into_iter().enumerate() {
+        let index = match name {
+            Some(name) => params
+                .iter()
+                .position(|param| param.name.0 == name)
+                .ok_or(UnmatchedArg::UnknownName),
+            None => taken
+                .iter()
+                .position(|&taken| !taken)
+                .ok_or(UnmatchedArg::TooMany),
+        };
+        if let Ok(index) = index {
+            taken[index] = true;
+            matched.params[index] = Some(arg);
+        }
+        matched.args.push(index);
+    }
+
+    matched
+}
+
+/// Finds the first call that cannot be resolved through any implicit conversions.
+fn diagnose(schema: &All, source_kind: &KindId, names: &[QualifiedRef]) -> ResolveError {
+    let mut kinds = BTreeSet::from([source_kind.clone()]);
+
+    for (call, name) in names.iter().enumerate() {
+        let kind = kinds.first().expect("kinds is nonempty").clone();
+
+        let reachable = implicit_closure(schema, kinds);
+        let next: BTreeSet<_> = reachable
+            .iter()
+            .filter_map(|kind| schema.mappings.get(kind))
+            .flat_map(|mappings| mappings.values())
+            .filter(|mapping| mapping.name.matches(name).is_some())
+            .map(|mapping| mapping.target_kind.clone())
+            .collect();
+
+        if next.is_empty() {
+            return ResolveError::UnknownMapping {
+                call,
+                name: name.clone(),
+                kind,
+            };
+        }
+        kinds = next;
+    }
+
+    ResolveError::Unadmitted {
+        kind: kinds.first().expect("kinds is nonempty").clone(),
+    }
+}
+
+/// Returns `kinds` together with all kinds reachable from them through implicit mappings.
+pub fn implicit_closure(schema: &All, kinds: BTreeSet<KindId>) -> BTreeSet<KindId> {
+    let mut queue: Vec<_> = kinds.iter().cloned().collect();
+    let mut reachable = kinds;
+
+    while let Some(kind) = queue.pop() {
+        for implicit in pathfind::implicit_mappings(schema, &kind) {
+            if reachable.insert(implicit.target_kind.clone()) {
+                queue.push(implicit.target_kind.clone());
+            }
+        }
+    }
+
+    reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::param;
+
+    fn params() -> Vec<ParamDef> {
+        serde_json::from_value(serde_json::Value::Array(vec![
+            param("a", KindId::STRING, false, false),
+            param("b", KindId::STRING, true, false),
+            param("c", KindId::STRING, true, true),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn match_positional_args() {
+        let matched = match_args_to_params(&params(), [None, None]);
+        assert_eq!(matched.args, [Ok(0), Ok(1)]);
+        assert_eq!(matched.params, [Some(0), Some(1), None]);
+    }
+
+    #[test]
+    fn positional_args_skip_named_params() {
+        let matched = match_args_to_params(&params(), [Some("b"), None, None]);
+        assert_eq!(matched.args, [Ok(1), Ok(0), Ok(2)]);
+        assert_eq!(matched.params, [Some(1), Some(0), Some(2)]);
+    }
+
+    #[test]
+    fn later_args_overwrite_earlier_args() {
+        // multi parameters do not collect multiple values on the server yet
+        let matched = match_args_to_params(&params(), [Some("c"), Some("c"), None, Some("a")]);
+        assert_eq!(matched.args, [Ok(2), Ok(2), Ok(0), Ok(0)]);
+        assert_eq!(matched.params, [Some(3), None, Some(1)]);
+    }
+
+    #[test]
+    fn unmatched_args() {
+        let matched = match_args_to_params(&params(), [Some("d"), None, None, None, None]);
+        assert_eq!(
+            matched.args,
+            [
+                Err(UnmatchedArg::UnknownName),
+                Ok(0),
+                Ok(1),
+                Ok(2),
+                Err(UnmatchedArg::TooMany)
+            ]
+        );
+        assert_eq!(matched.params, [Some(1), Some(2), Some(3)]);
+    }
+}
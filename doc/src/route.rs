@@ -0,0 +1,51 @@
+//! Routes of the app.
+//!
+//! A hash router is used because the site is served statically under a subdirectory.
+
+use serde::{Deserialize, Serialize};
+use yew_router::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Routable)]
+pub enum Route {
+    #[at("/")]
+    Editor,
+    #[at("/kinds")]
+    Kinds,
+    /// Kind IDs contain `/`, so the rest of the path is matched.
+    #[at("/kinds/*id")]
+    Kind { id: String },
+    #[at("/graph")]
+    Graph,
+    #[at("/diff")]
+    Diff,
+    /// Mapping names never contain `/`, unlike the source kind.
+    #[at("/mappings/:name/*kind")]
+    Mapping { name: String, kind: String },
+}
+
+/// Editor state stored in the query string of [`Route::Editor`] so that it can be shared as a link.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditorQuery {
+    /// The root kind of the template.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Comma-separated plugins unchecked in the plugin filter.
+    ///
+    /// Hidden plugins are stored instead of shown plugins
+    /// so that plugins added to the schema later are shown by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hide: Option<String>,
+    /// The template string, which encodes all paths and arguments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+impl EditorQuery {
+    pub fn hidden_plugins(&self) -> impl Iterator<Item = &str> {
+        self.hide
+            .as_deref()
+            .into_iter()
+            .flat_map(|hide| hide.split(','))
+            .filter(|plugin| !plugin.is_empty())
+    }
+}
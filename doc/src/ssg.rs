@@ -0,0 +1,251 @@
+//! The `infoapi-ssg` command line tool, which prerenders the kind reference into static HTML pages.
+//!
+//! Each page is written to `index.html` in the directory of its route,
+//! so that the links rendered by the router work on a static file server such as GitHub Pages.
+
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use anyhow::Context as _;
+use defy::defy;
+use yew::prelude::*;
+use yew_router::{
+    history::{AnyHistory, MemoryHistory},
+    prelude::*,
+};
+
+use crate::{
+    cli::{Arg, Args},
+    components,
+    data::{self, Data, KindId, MappingName},
+    route::Route,
+};
+
+const USAGE: &str = "\
+Usage: infoapi-ssg [OPTIONS] --schema <FILE>... --out <DIR>
+
+Prerenders one HTML page per kind and per mapping from schemas exported by `Doc::export`.
+
+Options:
+  -s, --schema <FILE>  A schema JSON file, may be repeated to merge multiple schemas
+  -o, --out <DIR>      The directory to write the pages into
+      --base <PATH>    The URL path that the directory is served at [default: /InfoAPI/reference]
+      --editor <URL>   The URL of the interactive editor [default: /InfoAPI/]
+  -h, --help           Print this message
+";
+
+const STYLESHEETS: &[&str] = &[
+    "https://cdn.jsdelivr.net/npm/bulma@0.9.4/css/bulma.min.css",
+    "https://cdn.jsdelivr.net/npm/@mdi/font@7.2.96/css/materialdesignicons.min.css",
+];
+
+pub fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(count) => {
+            eprintln!("wrote {count} pages to {}", options.out.display());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+struct Options {
+    schemas: Vec<String>,
+    out: PathBuf,
+    base: String,
+    editor: String,
+}
+
+impl Options {
+    /// Parses the command line arguments, or returns `None` if help is requested.
+    fn parse(args: impl Iterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        let mut schemas = Vec::new();
+        let mut out = None;
+        let mut base = "/InfoAPI/reference".to_string();
+        let mut editor = "/InfoAPI/".to_string();
+        let mut args = Args::new(args);
+
+        while let Some(arg) = args.next()? {
+            let flag = match arg {
+                Arg::Positional(arg) => anyhow::bail!("unexpected argument {arg}"),
+                Arg::Flag(flag) => flag,
+            };
+
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-s" | "--schema" => schemas.push(args.value()?),
+                "-o" | "--out" => out = Some(PathBuf::from(args.value()?)),
+                "--base" => base = args.value()?,
+                "--editor" => editor = args.value()?,
+                _ => anyhow::bail!("unknown option {flag}"),
+            }
+        }
+
+        anyhow::ensure!(!schemas.is_empty(), "no schema files given");
+        Ok(Some(Self {
+            schemas,
+            out: out.context("--out is required")?,
+            base: base.trim_end_matches('/').to_string(),
+            editor,
+        }))
+    }
+}
+
+/// Renders all pages, returning the number of pages written.
+fn run(options: &Options) -> anyhow::Result<usize> {
+    let schema = Data::from(data::load_files(&options.schemas)?);
+
+    let mut pages = vec![(Route::Kinds, "Kinds".to_string())];
+    for kind in schema.kinds.keys() {
+        pages.push((Route::Kind { id: kind.0.clone() }, kind.0.clone()));
+    }
+    for mapping in schema
+        .mappings
+        .values()
+        .flat_map(|mappings| mappings.values())
+    {
+        let route = Route::Mapping {
+            name: mapping.name.0.clone(),
+            kind: mapping.source_kind.0.clone(),
+        };
+        pages.push((
+            route,
+            format!("{} on {}", mapping.name.0, mapping.source_kind.0),
+        ));
+    }
+
+    for (route, title) in &pages {
+        let path = route.to_path();
+        let dir = page_dir(options, &path)?;
+        fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+
+        let body = render_page(PageProps {
+            schema: schema.clone(),
+            route: route.clone(),
+            base: options.base.clone(),
+            editor: options.editor.clone(),
+        });
+        let html = page_html(title, &body);
+
+        let file = dir.join("index.html");
+        fs::write(&file, html).with_context(|| format!("write {}", file.display()))?;
+    }
+
+    // the kind list doubles as the index of the reference
+    let index = options.out.join("index.html");
+    fs::copy(options.out.join("kinds/index.html"), &index)
+        .with_context(|| format!("write {}", index.display()))?;
+
+    Ok(pages.len() + 1)
+}
+
+/// The directory for the page at `path`, which must not escape the output directory.
+fn page_dir(options: &Options, path: &str) -> anyhow::Result<PathBuf> {
+    let mut dir = options.out.clone();
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        anyhow::ensure!(
+            segment != "." && segment != "..",
+            "cannot write a page for {path}"
+        );
+        dir.push(segment);
+    }
+    Ok(dir)
+}
+
+fn render_page(props: PageProps) -> String {
+    let renderer = yew::LocalServerRenderer::<StaticPage>::with_props(props).hydratable(false);
+    futures::executor::block_on(renderer.render())
+}
+
+fn page_html(title: &str, body: &str) -> String {
+    let stylesheets: String = STYLESHEETS
+        .iter()
+        .map(|href| format!(r#"    <link rel="stylesheet" href="{href}" />"#) + "\n")
+        .collect();
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+    <title>{title} - InfoAPI reference</title>
+{stylesheets}  </head>
+  <body>
+{body}
+  </body>
+</html>
+"#,
+        title = escape(title),
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// A page of the kind reference with a link back to the interactive editor.
+#[function_component]
+fn StaticPage(props: &PageProps) -> Html {
+    let history = AnyHistory::from(MemoryHistory::with_entries([props.route.to_path()]));
+
+    defy! {
+        Router(history = history, basename = Some(AttrValue::from(props.base.clone()))) {
+            nav(class = "navbar is-light") {
+                div(class = "navbar-brand") {
+                    Link<Route>(to = Route::Kinds, classes = classes!["navbar-item"]) {
+                        strong { + "InfoAPI reference"; }
+                    }
+                    a(class = "navbar-item", href = props.editor.clone()) {
+                        span(class = "icon") { i(class = "mdi mdi-pencil"); }
+                        span { + "Template editor"; }
+                    }
+                }
+            }
+
+            div(class = "section") {
+                div(class = "container") {
+                    match &props.route {
+                        Route::Kind { id } => {
+                            components::KindPage(schema = props.schema.clone(), kind = KindId(id.clone()));
+                        }
+                        Route::Mapping { name, kind } => {
+                            components::MappingPage(
+                                schema = props.schema.clone(),
+                                kind = KindId(kind.clone()),
+                                name = MappingName(name.clone()),
+                            );
+                        }
+                        _ => {
+                            components::KindList(schema = props.schema.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct PageProps {
+    schema: Data,
+    route: Route,
+    /// The URL path of the output directory, without a trailing slash.
+    base: String,
+    editor: String,
+}
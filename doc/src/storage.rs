@@ -0,0 +1,55 @@
+//! Persists editor sessions, saved templates and source settings in the browser local storage.
+
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::{data::SourceSettings, route::EditorQuery};
+
+const SESSION_KEY: &str = "infoapi-doc/session";
+const LIBRARY_KEY: &str = "infoapi-doc/templates";
+const SOURCES_KEY: &str = "infoapi-doc/sources";
+
+/// A template saved by the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Draft {
+    pub name: String,
+    pub state: EditorQuery,
+}
+
+/// The editor state when the page was last used, restored on startup.
+pub fn load_session() -> Option<EditorQuery> {
+    LocalStorage::get(SESSION_KEY).ok()
+}
+
+pub fn save_session(state: &EditorQuery) {
+    if let Err(err) = LocalStorage::set(SESSION_KEY, state) {
+        log::error!("cannot save session: {err}");
+    }
+}
+
+pub fn load_library() -> Vec<Draft> {
+    match LocalStorage::get(LIBRARY_KEY) {
+        Ok(drafts) => drafts,
+        Err(gloo::storage::errors::StorageError::KeyNotFound(_)) => Vec::new(),
+        Err(err) => {
+            log::error!("cannot load saved templates: {err}");
+            Vec::new()
+        }
+    }
+}
+
+pub fn save_library(drafts: &[Draft]) {
+    if let Err(err) = LocalStorage::set(LIBRARY_KEY, drafts) {
+        log::error!("cannot save templates: {err}");
+    }
+}
+
+pub fn load_source_settings() -> SourceSettings {
+    LocalStorage::get(SOURCES_KEY).unwrap_or_default()
+}
+
+pub fn save_source_settings(settings: &SourceSettings) {
+    if let Err(err) = LocalStorage::set(SOURCES_KEY, settings) {
+        log::error!("cannot save source settings: {err}");
+    }
+}
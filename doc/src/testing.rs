@@ -0,0 +1,58 @@
+//! Schema fixtures for unit tests.
+
+use serde_json::{json, Value};
+
+use crate::data::{parse_source, All};
+
+/// Builds a schema from `(kind, can_display)` pairs and mapping objects in the `Doc::export` format.
+pub fn schema(kinds: &[(&str, bool)], mappings: Vec<Value>) -> All {
+    let kinds: serde_json::Map<_, _> = kinds
+        .iter()
+        .map(|&(kind, can_display)| {
+            let def = json!({"help": kind, "canDisplay": can_display, "metadata": {}});
+            (kind.to_string(), def)
+        })
+        .collect();
+    let json = json!({
+        "formatVersion": 2,
+        "schema": {"kinds": kinds, "mappings": mappings},
+    });
+
+    let source = parse_source(&json.to_string(), "test").expect("fixture is valid");
+    assert!(source.skipped.is_empty(), "{:?}", source.skipped);
+    let mut schema = All::default();
+    schema.extend([Ok(source)]);
+    schema
+}
+
+/// A mapping from `source` to `target` without parameters.
+pub fn mapping(source: &str, name: &str, target: &str) -> Value {
+    json!({
+        "sourceKind": source,
+        "targetKind": target,
+        "name": name,
+        "isImplicit": false,
+        "parameters": [],
+        "mutable": false,
+        "help": name,
+        "metadata": {},
+    })
+}
+
+/// An implicit mapping from `source` to `target`.
+pub fn implicit(source: &str, name: &str, target: &str) -> Value {
+    let mut mapping = mapping(source, name, target);
+    mapping["isImplicit"] = true.into();
+    mapping
+}
+
+/// A parameter of `kind` for `mapping`'s `parameters`.
+pub fn param(name: &str, kind: &str, optional: bool, multi: bool) -> Value {
+    json!({"name": name, "kind": kind, "optional": optional, "multi": multi})
+}
+
+/// Replaces the parameters of `mapping`.
+pub fn with_params(mut mapping: Value, params: Vec<Value>) -> Value {
+    mapping["parameters"] = params.into();
+    mapping
+}
@@ -4,10 +4,49 @@ declare(strict_types=1);
 
 namespace SOFe\InfoAPI;
 
+use Composer\InstalledVersions;
 use stdClass;
+use function class_exists;
 
 final class Doc {
+	/**
+	 * The version of the exported JSON format.
+	 *
+	 * Version 1 exported the schema object directly without an envelope.
+	 */
+	public const FORMAT_VERSION = 2;
+
+	/**
+	 * Exports the schema of all registries without recording the generator.
+	 */
 	public static function export(Registries ...$registriesList) : mixed {
+		return self::exportWithGenerator(null, ...$registriesList);
+	}
+
+	/**
+	 * @param ?string $generator the plugin or tool that exports the schema
+	 */
+	public static function exportWithGenerator(?string $generator, Registries ...$registriesList) : mixed {
+		return [
+			"formatVersion" => self::FORMAT_VERSION,
+			"infoapiVersion" => self::infoapiVersion(),
+			"generator" => $generator,
+			"schema" => self::exportSchema(...$registriesList),
+		];
+	}
+
+	/**
+	 * The installed InfoAPI version, or null if it is not installed through composer, e.g. when shaded into a plugin.
+	 */
+	private static function infoapiVersion() : ?string {
+		if (!class_exists(InstalledVersions::class) || !InstalledVersions::isInstalled("sof3/infoapi")) {
+			return null;
+		}
+
+		return InstalledVersions::getPrettyVersion("sof3/infoapi");
+	}
+
+	private static function exportSchema(Registries ...$registriesList) : mixed {
 		$kinds = [];
 		foreach ($registriesList as $registries) {
 			foreach ($registries->kindMetas->getAll() as $help) {